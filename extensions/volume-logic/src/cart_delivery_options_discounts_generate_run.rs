@@ -6,12 +6,18 @@ use crate::schema::DeliveryDiscountSelectionStrategy;
 use crate::schema::DeliveryDiscountsAddOperation;
 use crate::schema::DeliveryGroupTarget;
 use crate::schema::DeliveryOperation;
+use crate::schema::DeliveryOptionTarget;
 use crate::schema::DiscountClass;
+use crate::schema::FixedAmount;
 use crate::schema::Percentage;
 
 use super::schema;
 use shopify_function::prelude::*;
 use shopify_function::Result;
+use volume_engine::cart;
+use volume_engine::decision::DeliveryTarget;
+use volume_engine::decision::DiscountValue;
+use volume_engine::delivery::delivery_discounts;
 
 #[shopify_function]
 fn cart_delivery_options_discounts_generate_run(
@@ -26,29 +32,153 @@ fn cart_delivery_options_discounts_generate_run(
         return Ok(CartDeliveryOptionsDiscountsGenerateRunResult { operations: vec![] });
     }
 
-    let first_delivery_group = input
-        .cart()
-        .delivery_groups()
-        .first()
-        .ok_or("No delivery groups found")?;
+    let cart = engine_cart(&input);
+    let discounts = delivery_discounts(&cart).unwrap_or_else(|error| {
+        log!(
+            "Skipping volume discounts, the discount's volume configuration is invalid: {}",
+            error
+        );
+        vec![]
+    });
+    let candidates: Vec<DeliveryDiscountCandidate> = discounts
+        .into_iter()
+        .map(|discount| DeliveryDiscountCandidate {
+            targets: vec![match discount.target {
+                DeliveryTarget::Group(id) => {
+                    DeliveryDiscountCandidateTarget::DeliveryGroup(DeliveryGroupTarget { id })
+                }
+                DeliveryTarget::Option(handle) => {
+                    DeliveryDiscountCandidateTarget::DeliveryOption(DeliveryOptionTarget { handle })
+                }
+            }],
+            value: match discount.value {
+                DiscountValue::Percentage(value) => {
+                    DeliveryDiscountCandidateValue::Percentage(Percentage {
+                        value: Decimal(value),
+                    })
+                }
+                DiscountValue::FixedAmount { amount, .. } => {
+                    DeliveryDiscountCandidateValue::FixedAmount(FixedAmount {
+                        amount: Decimal(amount),
+                    })
+                }
+            },
+            message: Some(discount.message),
+            associated_discount_code: None,
+        })
+        .collect();
 
-    Ok(CartDeliveryOptionsDiscountsGenerateRunResult {
-        operations: vec![DeliveryOperation::DeliveryDiscountsAdd(
+    let mut operations = vec![];
+    if !candidates.is_empty() {
+        operations.push(DeliveryOperation::DeliveryDiscountsAdd(
             DeliveryDiscountsAddOperation {
                 selection_strategy: DeliveryDiscountSelectionStrategy::All,
-                candidates: vec![DeliveryDiscountCandidate {
-                    targets: vec![DeliveryDiscountCandidateTarget::DeliveryGroup(
-                        DeliveryGroupTarget {
-                            id: first_delivery_group.id().clone(),
-                        },
-                    )],
-                    value: DeliveryDiscountCandidateValue::Percentage(Percentage {
-                        value: Decimal(100.0),
-                    }),
-                    message: Some("FREE DELIVERY".to_string()),
-                    associated_discount_code: None,
-                }],
+                candidates,
             },
-        )],
-    })
+        ));
+    }
+
+    Ok(CartDeliveryOptionsDiscountsGenerateRunResult { operations })
+}
+
+fn engine_cart(input: &schema::cart_delivery_options_discounts_generate_run::Input) -> cart::Cart {
+    cart::Cart {
+        lines: vec![],
+        presentment_currency_rate: input.presentment_currency_rate().as_f64(),
+        currency_code: Some(input.cart().cost().total_amount().currency_code().clone()),
+        language: Some(input.localization().language().iso_code().clone()),
+        delivery_groups: input
+            .cart()
+            .delivery_groups()
+            .iter()
+            .map(|delivery_group| cart::DeliveryGroup {
+                id: delivery_group.id().clone(),
+                line_ids: delivery_group
+                    .cart_lines()
+                    .iter()
+                    .map(|line| line.id().clone())
+                    .collect(),
+                options: delivery_group
+                    .delivery_options()
+                    .iter()
+                    .map(|option| cart::DeliveryOption {
+                        handle: option.handle().clone(),
+                        method: delivery_method(option.delivery_method_type()),
+                        cost: option.cost().amount().as_f64(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+        deliverable_lines: input
+            .cart()
+            .deliverable_lines()
+            .iter()
+            .map(|line| cart::DeliverableLine {
+                id: line.id().clone(),
+                quantity: *line.quantity(),
+                merchandise: engine_merchandise(line.merchandise()),
+            })
+            .collect(),
+        discount_config: input
+            .discount()
+            .volume_discount_config()
+            .map(|metafield| metafield.value().clone()),
+        purchasing_company: None,
+        customer: None,
+        market: None,
+        country: None,
+    }
+}
+
+fn delivery_method(method: &schema::DeliveryMethod) -> Option<cart::DeliveryMethod> {
+    match method {
+        schema::DeliveryMethod::Shipping => Some(cart::DeliveryMethod::Shipping),
+        schema::DeliveryMethod::PickUp => Some(cart::DeliveryMethod::PickUp),
+        schema::DeliveryMethod::PickupPoint => Some(cart::DeliveryMethod::PickupPoint),
+        schema::DeliveryMethod::Local => Some(cart::DeliveryMethod::Local),
+        schema::DeliveryMethod::Retail => Some(cart::DeliveryMethod::Retail),
+        schema::DeliveryMethod::None => Some(cart::DeliveryMethod::None),
+        schema::DeliveryMethod::Other => None,
+    }
+}
+
+// Only what exclusions need.
+fn engine_merchandise(
+    merchandise: &schema::cart_delivery_options_discounts_generate_run::input::cart::deliverable_lines::Merchandise,
+) -> cart::Merchandise {
+    use schema::cart_delivery_options_discounts_generate_run::input::cart::deliverable_lines::Merchandise;
+
+    match merchandise {
+        Merchandise::ProductVariant(variant) => {
+            cart::Merchandise::Variant(Box::new(cart::Variant {
+                id: variant.id().clone(),
+                product: cart::Product {
+                    id: variant.product().id().clone(),
+                    is_gift_card: *variant.product().is_gift_card(),
+                    ..cart::Product::default()
+                },
+                sku: variant.sku().cloned(),
+                requires_shipping: *variant.requires_shipping(),
+                ..cart::Variant::default()
+            }))
+        }
+        Merchandise::CustomProduct(custom) => cart::Merchandise::Custom(cart::CustomProduct {
+            is_gift_card: *custom.is_gift_card(),
+            requires_shipping: *custom.requires_shipping(),
+        }),
+        _ => cart::Merchandise::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_fixtures() {
+        crate::test_fixtures::replay(
+            "cart_delivery_options_discounts_generate_run",
+            cart_delivery_options_discounts_generate_run,
+        );
+    }
 }
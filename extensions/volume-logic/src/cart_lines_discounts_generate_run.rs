@@ -1,15 +1,20 @@
+use crate::schema::CartLineMinimumQuantity;
 use crate::schema::CartLineTarget;
 use crate::schema::CartLinesDiscountsGenerateRunResult;
 use crate::schema::CartOperation;
+use crate::schema::Condition;
 use crate::schema::DiscountClass;
+use crate::schema::FixedAmount;
 use crate::schema::OrderDiscountCandidate;
 use crate::schema::OrderDiscountCandidateTarget;
 use crate::schema::OrderDiscountCandidateValue;
 use crate::schema::OrderDiscountSelectionStrategy;
 use crate::schema::OrderDiscountsAddOperation;
+use crate::schema::OrderMinimumSubtotal;
 use crate::schema::OrderSubtotalTarget;
 use crate::schema::Percentage;
 use crate::schema::ProductDiscountCandidate;
+use crate::schema::ProductDiscountCandidateFixedAmount;
 use crate::schema::ProductDiscountCandidateTarget;
 use crate::schema::ProductDiscountCandidateValue;
 use crate::schema::ProductDiscountSelectionStrategy;
@@ -18,24 +23,18 @@ use crate::schema::ProductDiscountsAddOperation;
 use super::schema;
 use shopify_function::prelude::*;
 use shopify_function::Result;
+use volume_engine::cart;
+use volume_engine::config::DiscountConfig;
+use volume_engine::config::WeightUnit;
+use volume_engine::decision::DiscountValue;
+use volume_engine::decision::OrderCondition;
+use volume_engine::order::order_discounts;
+use volume_engine::product::product_discounts;
 
 #[shopify_function]
 fn cart_lines_discounts_generate_run(
     input: schema::cart_lines_discounts_generate_run::Input,
 ) -> Result<CartLinesDiscountsGenerateRunResult> {
-    let max_cart_line = input
-        .cart()
-        .lines()
-        .iter()
-        .max_by(|a, b| {
-            a.cost()
-                .subtotal_amount()
-                .amount()
-                .partial_cmp(b.cost().subtotal_amount().amount())
-                .unwrap_or(std::cmp::Ordering::Equal)
-        })
-        .ok_or("No cart lines found")?;
-
     let has_order_discount_class = input
         .discount()
         .discount_classes()
@@ -49,49 +48,259 @@ fn cart_lines_discounts_generate_run(
         return Ok(CartLinesDiscountsGenerateRunResult { operations: vec![] });
     }
 
+    let cart = engine_cart(&input);
+    // Reported here, once, whichever classes the discount has.
+    if let Some(Err(error)) = DiscountConfig::from_cart(&cart) {
+        log!(
+            "Skipping volume discounts, the discount's volume configuration is invalid: {}",
+            error
+        );
+    }
     let mut operations = vec![];
 
     // Check if the discount has the ORDER class
     if has_order_discount_class {
-        operations.push(CartOperation::OrderDiscountsAdd(
-            OrderDiscountsAddOperation {
-                selection_strategy: OrderDiscountSelectionStrategy::First,
-                candidates: vec![OrderDiscountCandidate {
-                    targets: vec![OrderDiscountCandidateTarget::OrderSubtotal(
-                        OrderSubtotalTarget {
-                            excluded_cart_line_ids: vec![],
-                        },
-                    )],
-                    message: Some("10% OFF ORDER".to_string()),
-                    value: OrderDiscountCandidateValue::Percentage(Percentage {
-                        value: Decimal(10.0),
-                    }),
-                    conditions: None,
-                    associated_discount_code: None,
-                }],
-            },
-        ));
+        let candidates: Vec<OrderDiscountCandidate> = order_discounts(&cart)
+            .into_iter()
+            .map(|discount| OrderDiscountCandidate {
+                targets: vec![OrderDiscountCandidateTarget::OrderSubtotal(
+                    OrderSubtotalTarget {
+                        excluded_cart_line_ids: discount.excluded_line_ids,
+                    },
+                )],
+                message: Some(discount.message),
+                value: match discount.value {
+                    DiscountValue::Percentage(value) => {
+                        OrderDiscountCandidateValue::Percentage(Percentage {
+                            value: Decimal(value),
+                        })
+                    }
+                    DiscountValue::FixedAmount { amount, .. } => {
+                        OrderDiscountCandidateValue::FixedAmount(FixedAmount {
+                            amount: Decimal(amount),
+                        })
+                    }
+                },
+                conditions: Some(discount.conditions.into_iter().map(condition).collect()),
+                associated_discount_code: None,
+            })
+            .collect();
+
+        if !candidates.is_empty() {
+            operations.push(CartOperation::OrderDiscountsAdd(
+                OrderDiscountsAddOperation {
+                    selection_strategy: OrderDiscountSelectionStrategy::Maximum,
+                    candidates,
+                },
+            ));
+        }
     }
 
     // Check if the discount has the PRODUCT class
     if has_product_discount_class {
-        operations.push(CartOperation::ProductDiscountsAdd(
-            ProductDiscountsAddOperation {
-                selection_strategy: ProductDiscountSelectionStrategy::First,
-                candidates: vec![ProductDiscountCandidate {
-                    targets: vec![ProductDiscountCandidateTarget::CartLine(CartLineTarget {
-                        id: max_cart_line.id().clone(),
-                        quantity: None,
-                    })],
-                    message: Some("20% OFF PRODUCT".to_string()),
-                    value: ProductDiscountCandidateValue::Percentage(Percentage {
-                        value: Decimal(20.0),
-                    }),
-                    associated_discount_code: None,
-                }],
-            },
-        ));
+        let decision = product_discounts(&cart);
+        for (owner_id, error) in &decision.override_errors {
+            log!(
+                "Ignoring volume discount overrides on {}: {}",
+                owner_id,
+                error
+            );
+        }
+        for (product_id, error) in &decision.config_errors {
+            log!(
+                "Skipping volume discount for product {}: {}",
+                product_id,
+                error
+            );
+        }
+
+        let candidates: Vec<ProductDiscountCandidate> = decision
+            .discounts
+            .into_iter()
+            .map(|discount| ProductDiscountCandidate {
+                targets: vec![ProductDiscountCandidateTarget::CartLine(CartLineTarget {
+                    id: discount.line_id,
+                    quantity: None,
+                })],
+                message: Some(discount.message),
+                value: match discount.value {
+                    DiscountValue::Percentage(value) => {
+                        ProductDiscountCandidateValue::Percentage(Percentage {
+                            value: Decimal(value),
+                        })
+                    }
+                    DiscountValue::FixedAmount {
+                        amount,
+                        applies_to_each_item,
+                    } => ProductDiscountCandidateValue::FixedAmount(
+                        ProductDiscountCandidateFixedAmount {
+                            amount: Decimal(amount),
+                            applies_to_each_item: Some(applies_to_each_item),
+                        },
+                    ),
+                },
+                associated_discount_code: None,
+            })
+            .collect();
+
+        if !candidates.is_empty() {
+            operations.push(CartOperation::ProductDiscountsAdd(
+                ProductDiscountsAddOperation {
+                    selection_strategy: ProductDiscountSelectionStrategy::All,
+                    candidates,
+                },
+            ));
+        }
     }
 
     Ok(CartLinesDiscountsGenerateRunResult { operations })
 }
+
+fn condition(condition: OrderCondition) -> Condition {
+    match condition {
+        OrderCondition::MinimumSubtotal {
+            amount,
+            excluded_line_ids,
+        } => Condition::OrderMinimumSubtotal(OrderMinimumSubtotal {
+            excluded_cart_line_ids: excluded_line_ids,
+            minimum_amount: Decimal(amount),
+        }),
+        OrderCondition::MinimumQuantity { quantity, line_ids } => {
+            Condition::CartLineMinimumQuantity(CartLineMinimumQuantity {
+                ids: line_ids,
+                minimum_quantity: quantity,
+            })
+        }
+    }
+}
+
+fn engine_cart(input: &schema::cart_lines_discounts_generate_run::Input) -> cart::Cart {
+    cart::Cart {
+        lines: input.cart().lines().iter().map(engine_line).collect(),
+        presentment_currency_rate: input.presentment_currency_rate().as_f64(),
+        currency_code: Some(input.cart().cost().total_amount().currency_code().clone()),
+        language: Some(input.localization().language().iso_code().clone()),
+        delivery_groups: vec![],
+        deliverable_lines: vec![],
+        discount_config: input
+            .discount()
+            .volume_discount_config()
+            .map(|metafield| metafield.value().clone()),
+        purchasing_company: input
+            .cart()
+            .buyer_identity()
+            .and_then(|buyer_identity| buyer_identity.purchasing_company())
+            .map(|purchasing_company| cart::PurchasingCompany {
+                company: cart::TierSetOwner {
+                    id: purchasing_company.company().id().clone(),
+                    volume_discount_overrides: purchasing_company
+                        .company()
+                        .volume_discount_overrides()
+                        .map(|metafield| metafield.value().clone()),
+                },
+                location: cart::TierSetOwner {
+                    id: purchasing_company.location().id().clone(),
+                    volume_discount_overrides: purchasing_company
+                        .location()
+                        .volume_discount_overrides()
+                        .map(|metafield| metafield.value().clone()),
+                },
+            }),
+        customer: input
+            .cart()
+            .buyer_identity()
+            .and_then(|buyer_identity| buyer_identity.customer())
+            .map(|customer| cart::Customer {
+                tags: customer
+                    .has_tags()
+                    .iter()
+                    .filter(|response| *response.has_tag())
+                    .map(|response| response.tag().clone())
+                    .collect(),
+                number_of_orders: i64::from(*customer.number_of_orders()),
+                amount_spent: customer.amount_spent().amount().as_f64(),
+            }),
+        market: Some(cart::TierSetOwner {
+            id: input.localization().market().id().clone(),
+            volume_discount_overrides: input
+                .localization()
+                .market()
+                .volume_discount_overrides()
+                .map(|metafield| metafield.value().clone()),
+        }),
+        country: Some(input.localization().country().iso_code().clone()),
+    }
+}
+
+fn engine_line(line: &schema::cart_lines_discounts_generate_run::input::cart::Lines) -> cart::Line {
+    let merchandise = match line.merchandise() {
+        schema::cart_lines_discounts_generate_run::input::cart::lines::Merchandise::ProductVariant(
+            variant,
+        ) => cart::Merchandise::Variant(Box::new(cart::Variant {
+            id: variant.id().clone(),
+            product: cart::Product {
+                id: variant.product().id().clone(),
+                title: variant.product().title().clone(),
+                volume_discount: variant
+                    .product()
+                    .volume_discount()
+                    .map(|metafield| metafield.value().clone()),
+                in_discount_collections: *variant.product().in_discount_collections(),
+                tags: variant
+                    .product()
+                    .has_tags()
+                    .iter()
+                    .filter(|response| *response.has_tag())
+                    .map(|response| response.tag().clone())
+                    .collect(),
+                vendor: variant.product().vendor().cloned(),
+                product_type: variant.product().product_type().cloned(),
+                is_gift_card: *variant.product().is_gift_card(),
+            },
+            weight: match (weight_unit(variant.weight_unit()), variant.weight()) {
+                (Some(weight_unit), Some(weight)) => weight_unit.to_kilograms(*weight),
+                _ => 0.0,
+            },
+            sku: variant.sku().cloned(),
+            requires_shipping: *variant.requires_shipping(),
+        })),
+        schema::cart_lines_discounts_generate_run::input::cart::lines::Merchandise::CustomProduct(
+            custom,
+        ) => cart::Merchandise::Custom(cart::CustomProduct {
+            is_gift_card: *custom.is_gift_card(),
+            requires_shipping: *custom.requires_shipping(),
+        }),
+        _ => cart::Merchandise::Other,
+    };
+
+    cart::Line {
+        id: line.id().clone(),
+        quantity: *line.quantity(),
+        unit_amount: line.cost().amount_per_quantity().amount().as_f64(),
+        subtotal: line.cost().subtotal_amount().amount().as_f64(),
+        merchandise,
+    }
+}
+
+fn weight_unit(weight_unit: &schema::WeightUnit) -> Option<WeightUnit> {
+    match weight_unit {
+        schema::WeightUnit::Grams => Some(WeightUnit::Grams),
+        schema::WeightUnit::Kilograms => Some(WeightUnit::Kilograms),
+        schema::WeightUnit::Ounces => Some(WeightUnit::Ounces),
+        schema::WeightUnit::Pounds => Some(WeightUnit::Pounds),
+        schema::WeightUnit::Other => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_fixtures() {
+        crate::test_fixtures::replay(
+            "cart_lines_discounts_generate_run",
+            cart_lines_discounts_generate_run,
+        );
+    }
+}
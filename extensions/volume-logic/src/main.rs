@@ -4,6 +4,9 @@ use std::process;
 pub mod cart_delivery_options_discounts_generate_run;
 pub mod cart_lines_discounts_generate_run;
 
+#[cfg(test)]
+mod test_fixtures;
+
 #[typegen("schema.graphql")]
 pub mod schema {
     #[query("src/cart_lines_discounts_generate_run.graphql")]
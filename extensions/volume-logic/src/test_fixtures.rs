@@ -0,0 +1,94 @@
+//! Replays the `tests/fixtures/*.json` run logs natively, so the exports can
+//! be checked with `cargo test` instead of a Wasm build and the JS helpers.
+
+use serde_json::Value;
+use shopify_function::wasm_api::{Context, Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Runs `function` on the input of every fixture recorded for `export` and
+/// compares the serialized result with the fixture's expected output.
+pub fn replay<F, P, O>(export: &str, function: F)
+where
+    F: Fn(P) -> shopify_function::Result<O>,
+    P: Deserialize,
+    O: Serialize,
+{
+    let fixtures = load_fixtures(export);
+    assert!(!fixtures.is_empty(), "no fixtures found for `{export}`");
+
+    let mut failures = vec![];
+    for (name, payload) in fixtures {
+        let output = match shopify_function::run_function_with_input(
+            &function,
+            &payload["input"].to_string(),
+        ) {
+            Ok(output) => serialize(&output),
+            Err(error) => {
+                failures.push(format!("{name}: function returned an error: {error}"));
+                continue;
+            }
+        };
+
+        // The Function runner writes omitted optional fields as explicit
+        // nulls, which GraphQL treats the same as leaving them out.
+        if without_nulls(&output) != without_nulls(&payload["output"]) {
+            failures.push(format!(
+                "{name}: output doesn't match\nexpected: {}\nactual:   {}",
+                serde_json::to_string_pretty(&payload["output"]).unwrap(),
+                serde_json::to_string_pretty(&output).unwrap(),
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
+
+fn load_fixtures(export: &str) -> Vec<(String, Value)> {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut paths: Vec<_> = fs::read_dir(&fixtures_dir)
+        .unwrap_or_else(|error| panic!("can't read {}: {error}", fixtures_dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let contents = fs::read_to_string(&path).unwrap();
+            let fixture: Value = serde_json::from_str(&contents)
+                .unwrap_or_else(|error| panic!("{} isn't valid JSON: {error}", path.display()));
+            let payload = fixture["payload"].clone();
+            (payload["export"] == export).then(|| {
+                (
+                    path.file_name().unwrap().to_string_lossy().into_owned(),
+                    payload,
+                )
+            })
+        })
+        .collect()
+}
+
+fn serialize<O: Serialize>(output: &O) -> Value {
+    let mut context = Context::new_with_input(serde_json::json!({}));
+    output.serialize(&mut context).unwrap();
+    context.finalize_output_and_return().unwrap()
+}
+
+fn without_nulls(value: &Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key.clone(), without_nulls(value)))
+                .collect(),
+        ),
+        Value::Array(array) => Value::Array(array.iter().map(without_nulls).collect()),
+        _ => value.clone(),
+    }
+}
@@ -0,0 +1,158 @@
+//! Synthetic Function inputs, shaped like the exports' input queries.
+
+use serde_json::{json, Value};
+
+// Realistic metaobject copies: a few tiers per set, mixed value kinds and
+// aggregation modes, shared across many products.
+const TIER_SETS: [&str; 4] = [
+    r#"{"version":1,"id":"gid://shopify/Metaobject/1","aggregation":"product","tiers":[{"qty":5,"discount":5.0,"label":"{product_title}: buy {qty}+, save {discount}, {saved_amount} in total","labels":{"de":"Ab {qty} Stück {percent}% sparen","fr":"Dès {qty}, {percent}% de remise"}},{"qty":10,"discount":10.0,"label":"{product_title}: buy {qty}+, save {discount}, {saved_amount} in total","labels":{"de":"Ab {qty} Stück {percent}% sparen","fr":"Dès {qty}, {percent}% de remise"}},{"qty":25,"discount":15.0,"label":"{product_title}: buy {qty}+, save {discount}, {saved_amount} in total","labels":{"de":"Ab {qty} Stück {percent}% sparen","fr":"Dès {qty}, {percent}% de remise"}},{"qty":50,"discount":20.0,"label":"{product_title}: buy {qty}+, save {discount}, {saved_amount} in total","labels":{"de":"Ab {qty} Stück {percent}% sparen","fr":"Dès {qty}, {percent}% de remise"}}]}"#,
+    r#"{"version":1,"id":"gid://shopify/Metaobject/2","aggregation":"metaobject","tiers":[{"qty":12,"kind":"fixed_amount","discount":1.5,"label":"$1.50 off each at 12+"},{"qty":48,"kind":"fixed_amount","discount":2.5,"label":"$2.50 off each at 48+"},{"spend":500.0,"discount":8.0,"label":"Spend $500, save 8%"}]}"#,
+    r#"{"version":1,"id":"gid://shopify/Metaobject/3","aggregation":"metaobject","weight_unit":"kilograms","tiers":[{"weight":5.0,"discount":5.0,"label":"5 kg+, save 5%"},{"weight":20.0,"discount":12.0,"label":"20 kg+, save 12%"}]}"#,
+    r#"[{"qty":50,"kind":"unit_price","price":8.5,"label":"$8.50 each at 50+"},{"qty":100,"kind":"unit_price","price":7.75,"label":"$7.75 each at 100+"}]"#,
+];
+
+// Collection, order and delivery tiers, customer segments, a targeting rule
+// and exclusions from the discount's own configuration.
+const DISCOUNT_CONFIG: &str = r#"{"version":1,"collection_ids":["gid://shopify/Collection/1","gid://shopify/Collection/2"],"rule":{"all":[{"any":[{"has_any_tag":["wholesale","bulk"]},{"product_type":["Fasteners"]}]},{"not":{"vendor":["Clearance Co"]}}]},"rule_tags":["wholesale","bulk"],"exclude":{"variant_ids":["gid://shopify/ProductVariant/3","gid://shopify/ProductVariant/30"],"sku_prefixes":["CLR-","OUTLET-"],"gift_cards":true},"tiers":[{"qty":10,"discount":5.0,"label":"Mix 10+, save 5%"},{"qty":40,"discount":10.0,"label":"Mix 40+, save 10%"}],"order_tiers":[{"qty":100,"discount":3.0,"label":"100+ items, save 3%"},{"qty":500,"discount":6.0,"label":"500+ items, save 6%"},{"spend":2500.0,"kind":"fixed_amount","discount":100.0,"currencies":{"EUR":{"spend":2300.0,"discount":90.0}},"label":"Spend $2500, get $100 off"}],"delivery":[{"methods":["pick_up"],"tiers":[{"qty":1,"discount":100.0,"label":"Free wholesale pickup"}]},{"methods":["shipping"],"handles":["standard"],"tiers":[{"qty":12,"discount":50.0,"label":"50% off shipping at 12+"},{"qty":24,"discount":100.0,"label":"Free shipping at 24+"}]},{"handles":["express"],"tiers":[{"qty":24,"kind":"fixed_amount","discount":15.0,"label":"Up to $15 off express at 24+"}]}],"customer_tags":["VIP","Wholesale"],"segments":[{"customer_tags":["VIP"],"tiers":[{"qty":10,"discount":12.0,"label":"VIP: buy 10+, save 12%"},{"qty":30,"discount":18.0,"label":"VIP: buy 30+, save 18%"}]},{"min_number_of_orders":5,"min_amount_spent":1000.0,"tiers":[{"qty":3,"discount":3.0,"label":"Loyal: buy 3+, save 3%"}]}]}"#;
+
+// A B2B buyer whose location and company both replace some of the shared
+// tier sets.
+const COMPANY_OVERRIDES: &str = r#"{"version":1,"tier_sets":{"gid://shopify/Metaobject/1":{"version":1,"aggregation":"product","tiers":[{"qty":5,"discount":8.0,"label":"Trade: buy 5+, save 8%"},{"qty":25,"discount":18.0,"label":"Trade: buy 25+, save 18%"}]},"gid://shopify/Product/4":[{"qty":10,"discount":12.0,"label":"Trade: buy 10+, save 12%"}]}}"#;
+const LOCATION_OVERRIDES: &str = r#"{"version":1,"tier_sets":{"gid://shopify/Metaobject/2":{"version":1,"aggregation":"metaobject","tiers":[{"qty":12,"discount":10.0,"label":"Distributor: buy 12+, save 10%"},{"qty":48,"discount":22.0,"label":"Distributor: buy 48+, save 22%"}]}}}"#;
+
+// Market-wide breaks with country-specific ones for the buyer's country.
+const MARKET_OVERRIDES: &str = r#"{"version":1,"tier_sets":{"gid://shopify/Metaobject/3":{"version":1,"aggregation":"metaobject","weight_unit":"kilograms","tiers":[{"weight":10.0,"discount":6.0,"label":"10 kg+, save 6%"},{"weight":40.0,"discount":14.0,"label":"40 kg+, save 14%"}]}},"countries":{"DE":{"gid://shopify/Metaobject/1":{"version":1,"aggregation":"product","tiers":[{"qty":6,"discount":6.0,"label":"Ab 6 Stück 6% sparen"},{"qty":24,"discount":16.0,"label":"Ab 24 Stück 16% sparen"}]}},"FR":{"gid://shopify/Metaobject/1":[{"qty":8,"discount":7.0,"label":"Dès 8, 7% de remise"}]}}}"#;
+
+/// Input for `cart_lines_discounts_generate_run` with `line_count` lines.
+pub fn cart_lines_input(line_count: usize) -> Value {
+    let lines: Vec<Value> = (0..line_count)
+        .map(|index| {
+            let quantity = 1 + (index * 7) % 60;
+            let unit_amount = 4.0 + (index % 23) as f64 * 1.25;
+            let tier_set = TIER_SETS[index % TIER_SETS.len()];
+            json!({
+                "id": format!("gid://shopify/CartLine/{index}"),
+                "quantity": quantity,
+                "cost": {
+                    "amountPerQuantity": { "amount": format!("{unit_amount:.2}") },
+                    "subtotalAmount": { "amount": format!("{:.2}", unit_amount * quantity as f64) },
+                },
+                "merchandise": {
+                    "__typename": "ProductVariant",
+                    "id": format!("gid://shopify/ProductVariant/{index}"),
+                    "sku": if index % 13 == 0 { format!("CLR-{index:05}") } else { format!("FUI-{index:05}") },
+                    "requiresShipping": true,
+                    "weight": 250.0 + (index % 4) as f64 * 250.0,
+                    "weightUnit": "GRAMS",
+                    "product": {
+                        "id": format!("gid://shopify/Product/{}", index / 3),
+                        "title": format!("Fosterui Part {}", index / 3),
+                        "volumeDiscount": (index % 5 != 4).then(|| json!({ "value": tier_set })),
+                        "inDiscountCollections": index % 2 == 0,
+                        "hasTags": [
+                            { "tag": "wholesale", "hasTag": index % 3 != 0 },
+                            { "tag": "bulk", "hasTag": index % 7 == 0 },
+                        ],
+                        "vendor": if index % 11 == 0 { "Clearance Co" } else { "Acme" },
+                        "productType": if index % 2 == 0 { "Fasteners" } else { "Hardware" },
+                        "isGiftCard": false,
+                    },
+                },
+            })
+        })
+        .collect();
+
+    json!({
+        "cart": {
+            "cost": { "totalAmount": { "currencyCode": "EUR" } },
+            "lines": lines,
+            "buyerIdentity": {
+                "customer": {
+                    "hasTags": [
+                        { "tag": "VIP", "hasTag": true },
+                        { "tag": "Wholesale", "hasTag": false },
+                    ],
+                    "numberOfOrders": 12,
+                    "amountSpent": { "amount": "4210.50" },
+                },
+                "purchasingCompany": {
+                    "company": {
+                        "id": "gid://shopify/Company/1",
+                        "volumeDiscountOverrides": { "value": COMPANY_OVERRIDES },
+                    },
+                    "location": {
+                        "id": "gid://shopify/CompanyLocation/1",
+                        "volumeDiscountOverrides": { "value": LOCATION_OVERRIDES },
+                    },
+                },
+            },
+        },
+        "discount": {
+            "discountClasses": ["PRODUCT", "ORDER"],
+            "volumeDiscountConfig": { "value": DISCOUNT_CONFIG },
+        },
+        "localization": {
+            "country": { "isoCode": "DE" },
+            "language": { "isoCode": "DE" },
+            "market": {
+                "id": "gid://shopify/Market/2",
+                "volumeDiscountOverrides": { "value": MARKET_OVERRIDES },
+            },
+        },
+        "presentmentCurrencyRate": "0.92",
+    })
+}
+
+/// Input for `cart_delivery_options_discounts_generate_run` for a cart with
+/// `line_count` lines.
+pub fn cart_delivery_options_input(line_count: usize) -> Value {
+    let deliverable_lines: Vec<Value> = (0..line_count)
+        .map(|index| {
+            json!({
+                "id": format!("gid://shopify/CartLine/{index}"),
+                "quantity": 1 + (index * 7) % 60,
+                "merchandise": {
+                    "__typename": "ProductVariant",
+                    "id": format!("gid://shopify/ProductVariant/{index}"),
+                    "sku": format!("FUI-{index:05}"),
+                    "requiresShipping": true,
+                    "product": {
+                        "id": format!("gid://shopify/Product/{}", index / 3),
+                        "isGiftCard": false,
+                    },
+                },
+            })
+        })
+        .collect();
+    let delivery_groups: Vec<Value> = (0..line_count.div_ceil(50))
+        .map(|index| {
+            json!({
+                "id": format!("gid://shopify/CartDeliveryGroup/{index}"),
+                "cartLines": (index * 50..line_count.min(index * 50 + 50))
+                    .map(|line| json!({ "id": format!("gid://shopify/CartLine/{line}") }))
+                    .collect::<Vec<_>>(),
+                "deliveryOptions": [
+                    { "handle": "standard", "deliveryMethodType": "SHIPPING", "cost": { "amount": "12.0" } },
+                    { "handle": "express", "deliveryMethodType": "SHIPPING", "cost": { "amount": "25.0" } },
+                    { "handle": "warehouse-pickup", "deliveryMethodType": "PICK_UP", "cost": { "amount": "0.0" } },
+                ],
+            })
+        })
+        .collect();
+
+    json!({
+        "cart": {
+            "cost": { "totalAmount": { "currencyCode": "EUR" } },
+            "deliverableLines": deliverable_lines,
+            "deliveryGroups": delivery_groups,
+        },
+        "discount": {
+            "discountClasses": ["SHIPPING"],
+            "volumeDiscountConfig": { "value": DISCOUNT_CONFIG },
+        },
+        "localization": {
+            "language": { "isoCode": "DE" },
+        },
+        "presentmentCurrencyRate": "0.92",
+    })
+}
@@ -0,0 +1,233 @@
+//! Measures the compiled volume-logic exports against the Shopify Functions
+//! resource limits.
+//!
+//! Runs each export on synthetic carts through Shopify's `function-runner`,
+//! reports the instruction count and peak memory of every run, and exits
+//! with a failure when any run goes over budget.
+//!
+//! ```shell
+//! cargo build --target=wasm32-unknown-unknown --release -p volume-logic
+//! cargo run -p volume-bench -- --max-instructions 11000000
+//! ```
+
+mod carts;
+
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+/// The instruction limit for a cart of up to 200 lines.
+const DEFAULT_MAX_INSTRUCTIONS: u64 = 11_000_000;
+/// The linear memory limit, in kilobytes. It doesn't scale with the cart.
+const DEFAULT_MAX_MEMORY_KB: u64 = 10_000;
+/// `@scaleLimits(rate: 0.005)` on `Cart.lines` and
+/// `CartDeliveryGroup.cartLines`: the instruction limit grows with the line
+/// count once the cart passes 200 lines, up to ten times the base limit.
+const CART_LINES_SCALE_RATE: f64 = 0.005;
+const MAX_SCALE_FACTOR: f64 = 10.0;
+
+type InputBuilder = fn(usize) -> Value;
+
+const EXPORTS: [(&str, &str, InputBuilder); 2] = [
+    (
+        "cart_lines_discounts_generate_run",
+        "src/cart_lines_discounts_generate_run.graphql",
+        carts::cart_lines_input,
+    ),
+    (
+        "cart_delivery_options_discounts_generate_run",
+        "src/cart_delivery_options_discounts_generate_run.graphql",
+        carts::cart_delivery_options_input,
+    ),
+];
+
+struct Options {
+    runner: PathBuf,
+    wasm: PathBuf,
+    sizes: Vec<usize>,
+    max_instructions: u64,
+    max_memory_kb: u64,
+    write_inputs: Option<PathBuf>,
+}
+
+fn main() {
+    let options = parse_options().unwrap_or_else(|error| {
+        eprintln!("volume-bench: {error}");
+        eprintln!(
+            "usage: volume-bench [--runner PATH] [--wasm PATH] [--sizes 1,50,200,500] \
+             [--max-instructions N] [--max-memory-kb N] [--write-inputs DIR]"
+        );
+        process::exit(2);
+    });
+
+    if let Some(dir) = &options.write_inputs {
+        write_inputs(&options, dir);
+        return;
+    }
+
+    if !options.wasm.exists() {
+        eprintln!(
+            "volume-bench: {} not found; build it with \
+             `cargo build --target=wasm32-unknown-unknown --release -p volume-logic`",
+            options.wasm.display()
+        );
+        process::exit(2);
+    }
+
+    println!(
+        "{:<46} {:>6} {:>14} {:>14} {:>11} {:>11}",
+        "export", "lines", "instructions", "budget", "memory KB", "budget"
+    );
+
+    let mut over_budget = false;
+    for (export, query, build_input) in EXPORTS {
+        for &size in &options.sizes {
+            let input = build_input(size);
+            let scale = scale_factor(&input);
+            let instruction_budget = (options.max_instructions as f64 * scale) as u64;
+            let memory_budget = options.max_memory_kb;
+
+            match run(&options, export, query, &input) {
+                Ok((instructions, memory_kb)) => {
+                    let failed = instructions > instruction_budget || memory_kb > memory_budget;
+                    over_budget |= failed;
+                    println!(
+                        "{:<46} {:>6} {:>14} {:>14} {:>11} {:>11}{}",
+                        export,
+                        size,
+                        instructions,
+                        instruction_budget,
+                        memory_kb,
+                        memory_budget,
+                        if failed { "  OVER BUDGET" } else { "" }
+                    );
+                }
+                Err(error) => {
+                    over_budget = true;
+                    println!("{export:<46} {size:>6}  failed: {error}");
+                }
+            }
+        }
+    }
+
+    if over_budget {
+        process::exit(1);
+    }
+}
+
+fn parse_options() -> Result<Options, String> {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut options = Options {
+        runner: env::var_os("FUNCTION_RUNNER")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("function-runner")),
+        wasm: manifest_dir.join("../target/wasm32-unknown-unknown/release/volume-logic.wasm"),
+        sizes: vec![1, 50, 200, 500],
+        max_instructions: DEFAULT_MAX_INSTRUCTIONS,
+        max_memory_kb: DEFAULT_MAX_MEMORY_KB,
+        write_inputs: None,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or(format!("{flag} needs a value"));
+        match flag.as_str() {
+            "--runner" => options.runner = PathBuf::from(value()?),
+            "--wasm" => options.wasm = PathBuf::from(value()?),
+            "--sizes" => {
+                options.sizes = value()?
+                    .split(',')
+                    .map(|size| {
+                        size.trim()
+                            .parse()
+                            .map_err(|_| format!("bad size `{size}`"))
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            "--max-instructions" => {
+                options.max_instructions = value()?.parse().map_err(|_| "bad instruction budget")?
+            }
+            "--max-memory-kb" => {
+                options.max_memory_kb = value()?.parse().map_err(|_| "bad memory budget")?
+            }
+            "--write-inputs" => options.write_inputs = Some(PathBuf::from(value()?)),
+            _ => return Err(format!("unknown argument `{flag}`")),
+        }
+    }
+
+    Ok(options)
+}
+
+// The instruction limit scales with the number of cart lines the input query
+// selects.
+fn scale_factor(input: &Value) -> f64 {
+    let cart = &input["cart"];
+    let line_count = match cart["lines"].as_array() {
+        Some(lines) => lines.len(),
+        None => cart["deliveryGroups"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|delivery_group| delivery_group["cartLines"].as_array())
+            .map(Vec::len)
+            .sum(),
+    };
+    (line_count as f64 * CART_LINES_SCALE_RATE).clamp(1.0, MAX_SCALE_FACTOR)
+}
+
+// Returns the instruction count and peak memory, in kilobytes, of one run.
+fn run(options: &Options, export: &str, query: &str, input: &Value) -> Result<(u64, u64), String> {
+    let function_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let input_path = env::temp_dir().join(format!("volume-bench-{export}-{}.json", process::id()));
+    fs::write(&input_path, input.to_string()).map_err(|error| error.to_string())?;
+
+    let output = Command::new(&options.runner)
+        .arg("--function")
+        .arg(&options.wasm)
+        .arg("--input")
+        .arg(&input_path)
+        .arg("--export")
+        .arg(export)
+        .arg("--schema-path")
+        .arg(function_dir.join("schema.graphql"))
+        .arg("--query-path")
+        .arg(function_dir.join(query))
+        .arg("--json")
+        .output();
+    let _ = fs::remove_file(&input_path);
+
+    let output =
+        output.map_err(|error| format!("can't run {}: {error}", options.runner.display()))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let report: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|error| format!("unexpected function-runner output: {error}"))?;
+    let field = |name: &str| {
+        report[name]
+            .as_u64()
+            .ok_or(format!("function-runner output has no `{name}`"))
+    };
+    Ok((field("instructions")?, field("memory_usage")?))
+}
+
+fn write_inputs(options: &Options, dir: &Path) {
+    fs::create_dir_all(dir).unwrap_or_else(|error| {
+        eprintln!("volume-bench: can't create {}: {error}", dir.display());
+        process::exit(2);
+    });
+
+    for (export, _, build_input) in EXPORTS {
+        for &size in &options.sizes {
+            let path = dir.join(format!("{export}-{size}.json"));
+            fs::write(&path, build_input(size).to_string()).unwrap_or_else(|error| {
+                eprintln!("volume-bench: can't write {}: {error}", path.display());
+                process::exit(2);
+            });
+            println!("{}", path.display());
+        }
+    }
+}
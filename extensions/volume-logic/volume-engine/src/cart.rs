@@ -0,0 +1,143 @@
+use serde::Deserialize;
+
+/// A cart as seen by the engine. Amounts are in the buyer's presentment
+/// currency unless noted otherwise.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cart {
+    pub lines: Vec<Line>,
+    /// Multiply a shop-currency amount by this to get the presentment amount.
+    pub presentment_currency_rate: f64,
+    /// The ISO 4217 code of the presentment currency, when known.
+    pub currency_code: Option<String>,
+    /// The buyer's language, as a Shopify `LanguageCode` such as `EN` or
+    /// `PT_BR`, when known.
+    pub language: Option<String>,
+    pub delivery_groups: Vec<DeliveryGroup>,
+    /// The lines that need delivering, across every delivery group.
+    pub deliverable_lines: Vec<DeliverableLine>,
+    /// The raw configuration from the discount's volume-discount metafield.
+    pub discount_config: Option<String>,
+    /// The company a B2B buyer purchases for, if any.
+    pub purchasing_company: Option<PurchasingCompany>,
+    /// The signed-in customer, if any.
+    pub customer: Option<Customer>,
+    /// The market of the buyer's localized experience.
+    pub market: Option<TierSetOwner>,
+    /// The ISO 3166-1 alpha-2 code of the buyer's country, when known.
+    pub country: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Customer {
+    /// The customer's tags, among those the discount's segments test.
+    pub tags: Vec<String>,
+    pub number_of_orders: i64,
+    /// The customer's lifetime spend, converted to the presentment currency.
+    pub amount_spent: f64,
+}
+
+/// A B2B buyer's company and the location they purchase for. Each can carry
+/// tier sets that replace the tiers of the products they buy.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PurchasingCompany {
+    pub company: TierSetOwner,
+    pub location: TierSetOwner,
+}
+
+/// A resource whose volume-discount overrides metafield can replace product
+/// tier sets.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TierSetOwner {
+    pub id: String,
+    /// The raw overrides from the resource's volume-discount overrides
+    /// metafield.
+    pub volume_discount_overrides: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line {
+    pub id: String,
+    pub quantity: i32,
+    /// The cost of a single unit.
+    pub unit_amount: f64,
+    pub subtotal: f64,
+    pub merchandise: Merchandise,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Merchandise {
+    Variant(Box<Variant>),
+    /// A custom product. The engine never prices these, but can exclude them
+    /// from order discounts.
+    Custom(CustomProduct),
+    /// Any other merchandise the engine doesn't know about.
+    Other,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Variant {
+    pub id: String,
+    pub product: Product,
+    /// The weight of a single unit, in kilograms. Zero when unknown.
+    pub weight: f64,
+    pub sku: Option<String>,
+    pub requires_shipping: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomProduct {
+    pub is_gift_card: bool,
+    pub requires_shipping: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Product {
+    pub id: String,
+    pub title: String,
+    /// The raw tier configuration from the product's volume-discount metafield.
+    pub volume_discount: Option<String>,
+    /// Whether the product is in any of the discount's configured collections.
+    pub in_discount_collections: bool,
+    /// The product's tags, among those the discount's rule tests.
+    pub tags: Vec<String>,
+    pub vendor: Option<String>,
+    pub product_type: Option<String>,
+    pub is_gift_card: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeliveryGroup {
+    pub id: String,
+    /// The IDs of the cart lines shipped together in this group.
+    pub line_ids: Vec<String>,
+    pub options: Vec<DeliveryOption>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeliveryOption {
+    pub handle: String,
+    /// `None` for methods the engine doesn't know about.
+    pub method: Option<DeliveryMethod>,
+    /// What the buyer pays for the option.
+    pub cost: f64,
+}
+
+/// How a delivery option reaches the buyer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryMethod {
+    Shipping,
+    PickUp,
+    PickupPoint,
+    Local,
+    Retail,
+    None,
+}
+
+/// A line that needs delivering. The delivery export doesn't see line costs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeliverableLine {
+    pub id: String,
+    pub quantity: i32,
+    pub merchandise: Merchandise,
+}
@@ -0,0 +1,1421 @@
+use crate::cart::Cart;
+use crate::cart::Customer;
+use crate::cart::DeliveryMethod;
+use crate::cart::Merchandise;
+use crate::cart::Product;
+use crate::currency::Currency;
+use crate::exclusion::Exclusions;
+use crate::message::MessageTemplate;
+use crate::message::MessageValues;
+use crate::message::Placeholder;
+use crate::rule::Rule;
+use serde::Deserialize;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::mem;
+
+/// The configuration schema version this crate understands.
+pub const CURRENT_VERSION: u64 = 1;
+
+/// How a tier's value is applied to a qualifying cart line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TierKind {
+    /// `discount` percent off the line.
+    #[default]
+    Percentage,
+    /// `discount` off each unit, in the cart currency.
+    FixedAmount,
+    /// Each unit costs `price`, in the cart currency.
+    UnitPrice,
+}
+
+impl fmt::Display for TierKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TierKind::Percentage => "percentage",
+            TierKind::FixedAmount => "fixed_amount",
+            TierKind::UnitPrice => "unit_price",
+        })
+    }
+}
+
+/// The value a tier applies, resolved from its `kind`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TierValue {
+    Percentage(f64),
+    FixedAmount(f64),
+    UnitPrice(f64),
+}
+
+impl TierValue {
+    /// Whether `self` is at least as generous as `other`. Values of
+    /// different kinds can't be compared without a price, so they're only
+    /// checked against tiers of the same kind.
+    fn is_at_least(&self, other: &TierValue) -> bool {
+        match (self, other) {
+            (TierValue::Percentage(a), TierValue::Percentage(b))
+            | (TierValue::FixedAmount(a), TierValue::FixedAmount(b)) => a >= b,
+            (TierValue::UnitPrice(a), TierValue::UnitPrice(b)) => a <= b,
+            _ => true,
+        }
+    }
+}
+
+/// What a group of cart lines has accumulated toward tier thresholds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Volume {
+    pub quantity: i32,
+    /// The combined subtotal, in the currency of the thresholds it's
+    /// compared against.
+    pub subtotal: f64,
+    /// The combined weight, in kilograms.
+    pub weight: f64,
+}
+
+/// The unit that `weight` thresholds are written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeightUnit {
+    Grams,
+    #[default]
+    Kilograms,
+    Ounces,
+    Pounds,
+}
+
+impl WeightUnit {
+    pub fn to_kilograms(self, weight: f64) -> f64 {
+        match self {
+            WeightUnit::Grams => weight / 1000.0,
+            WeightUnit::Kilograms => weight,
+            WeightUnit::Ounces => weight * 0.028_349_523_125,
+            WeightUnit::Pounds => weight * 0.453_592_37,
+        }
+    }
+}
+
+/// The volume a tier requires before it applies.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum Threshold {
+    /// At least this many units.
+    #[serde(rename = "qty")]
+    Quantity(i32),
+    /// At least this much spent, in the shop currency until converted.
+    #[serde(rename = "spend")]
+    Subtotal(f64),
+    /// At least this much weight, in kilograms.
+    #[serde(rename = "weight")]
+    Weight(f64),
+}
+
+impl Threshold {
+    pub fn is_met_by(&self, volume: &Volume) -> bool {
+        match *self {
+            Threshold::Quantity(quantity) => volume.quantity >= quantity,
+            Threshold::Subtotal(subtotal) => volume.subtotal >= subtotal,
+            Threshold::Weight(weight) => volume.weight >= weight,
+        }
+    }
+
+    /// How much `volume` falls short of the threshold, as a threshold of the
+    /// same kind.
+    pub fn remaining(&self, volume: &Volume) -> Threshold {
+        match *self {
+            Threshold::Quantity(quantity) => {
+                Threshold::Quantity((quantity - volume.quantity).max(0))
+            }
+            Threshold::Subtotal(subtotal) => {
+                Threshold::Subtotal((subtotal - volume.subtotal).max(0.0))
+            }
+            Threshold::Weight(weight) => Threshold::Weight((weight - volume.weight).max(0.0)),
+        }
+    }
+
+    fn field(&self) -> TierField {
+        match self {
+            Threshold::Quantity(_) => TierField::Qty,
+            Threshold::Subtotal(_) => TierField::Spend,
+            Threshold::Weight(_) => TierField::Weight,
+        }
+    }
+
+    // Groups thresholds by kind, and orders each kind by ascending amount.
+    // Amounts are validated as finite, so this is total.
+    fn sort_key(&self) -> (u8, f64) {
+        match *self {
+            Threshold::Quantity(quantity) => (0, f64::from(quantity)),
+            Threshold::Subtotal(subtotal) => (1, subtotal),
+            Threshold::Weight(weight) => (2, weight),
+        }
+    }
+}
+
+impl fmt::Display for Threshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Threshold::Quantity(quantity) => write!(f, "`qty` {quantity}"),
+            Threshold::Subtotal(subtotal) => write!(f, "`spend` {subtotal}"),
+            Threshold::Weight(weight) => write!(f, "`weight` {weight} kg"),
+        }
+    }
+}
+
+/// A single volume break: reach `threshold`, get `value`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TierConfig {
+    pub threshold: Threshold,
+    pub value: TierValue,
+    /// The message shown for the tier, unless a translation in `labels` suits
+    /// the buyer better.
+    pub label: MessageTemplate,
+    /// Translations of `label`, keyed by upper-case language code such as
+    /// `DE` or `PT_BR`.
+    pub labels: BTreeMap<String, MessageTemplate>,
+    /// Amounts to use instead of converting the shop-currency ones, keyed by
+    /// ISO 4217 currency code.
+    pub currencies: BTreeMap<String, CurrencyAmounts>,
+}
+
+impl TierConfig {
+    /// The tier with its spend threshold and fixed amounts in `currency`.
+    pub fn in_currency(&self, currency: &Currency) -> TierConfig {
+        let explicit = currency
+            .code
+            .and_then(|code| self.currencies.get(code))
+            .copied()
+            .unwrap_or_default();
+        TierConfig {
+            threshold: match self.threshold {
+                Threshold::Subtotal(subtotal) => {
+                    Threshold::Subtotal(currency.convert(subtotal, explicit.spend))
+                }
+                threshold => threshold,
+            },
+            value: match self.value {
+                TierValue::Percentage(percentage) => TierValue::Percentage(percentage),
+                TierValue::FixedAmount(amount) => {
+                    TierValue::FixedAmount(currency.convert(amount, explicit.discount))
+                }
+                TierValue::UnitPrice(price) => {
+                    TierValue::UnitPrice(currency.convert(price, explicit.price))
+                }
+            },
+            label: self.label.clone(),
+            labels: self.labels.clone(),
+            currencies: BTreeMap::new(),
+        }
+    }
+
+    /// The label for buyers reading `language`: its own translation, then the
+    /// translation for its base language, then `label`.
+    pub fn label_for(&self, language: Option<&str>) -> &MessageTemplate {
+        let Some(language) = language else {
+            return &self.label;
+        };
+        let base_language = language.split('_').next().unwrap_or(language);
+        self.labels
+            .get(language)
+            .or_else(|| self.labels.get(base_language))
+            .unwrap_or(&self.label)
+    }
+
+    /// The tier's message for buyers reading `language`. `values` supplies
+    /// what the tiers don't know themselves, such as the amount saved; the
+    /// rest comes from the tier and from `next_tier`, the tier above it, which
+    /// should both already be in the presentment currency. `volume` is what
+    /// the next tier's distance is measured from.
+    pub fn message(
+        &self,
+        language: Option<&str>,
+        next_tier: Option<&TierConfig>,
+        volume: &Volume,
+        values: MessageValues,
+    ) -> String {
+        let remaining = next_tier.map(|next_tier| next_tier.threshold.remaining(volume));
+        let values = MessageValues {
+            next_tier_qty: match next_tier.map(|next_tier| next_tier.threshold) {
+                Some(Threshold::Quantity(quantity)) => Some(quantity),
+                _ => None,
+            },
+            remaining_qty: match remaining {
+                Some(Threshold::Quantity(quantity)) => Some(quantity),
+                _ => None,
+            },
+            remaining_spend: match remaining {
+                Some(Threshold::Subtotal(subtotal)) => Some(subtotal),
+                _ => None,
+            },
+            next_tier_percent: match next_tier.map(|next_tier| next_tier.value) {
+                Some(TierValue::Percentage(percentage)) => Some(percentage),
+                _ => None,
+            },
+            next_tier_amount: match next_tier.map(|next_tier| next_tier.value) {
+                Some(TierValue::FixedAmount(amount) | TierValue::UnitPrice(amount)) => Some(amount),
+                _ => None,
+            },
+            tier_qty: match self.threshold {
+                Threshold::Quantity(quantity) => Some(quantity),
+                _ => None,
+            },
+            spend: match self.threshold {
+                Threshold::Subtotal(subtotal) => Some(subtotal),
+                _ => None,
+            },
+            percent: match self.value {
+                TierValue::Percentage(percentage) => Some(percentage),
+                _ => None,
+            },
+            amount: match self.value {
+                TierValue::FixedAmount(amount) => Some(amount),
+                _ => None,
+            },
+            price: match self.value {
+                TierValue::UnitPrice(price) => Some(price),
+                _ => None,
+            },
+            ..values
+        };
+        self.label_for(language).render(&values, language)
+    }
+
+    // Whether the tier has a value for `placeholder`, regardless of where its
+    // message is shown.
+    fn fills(&self, placeholder: Placeholder) -> bool {
+        match placeholder {
+            Placeholder::TierQty | Placeholder::NextTierQty => {
+                matches!(self.threshold, Threshold::Quantity(_))
+            }
+            Placeholder::Spend => matches!(self.threshold, Threshold::Subtotal(_)),
+            Placeholder::Percent => matches!(self.value, TierValue::Percentage(_)),
+            Placeholder::Amount => matches!(self.value, TierValue::FixedAmount(_)),
+            Placeholder::Price => matches!(self.value, TierValue::UnitPrice(_)),
+            // Weight tiers lose their unit once converted to kilograms.
+            Placeholder::NextTierRemaining => !matches!(self.threshold, Threshold::Weight(_)),
+            Placeholder::Discount
+            | Placeholder::SavedAmount
+            | Placeholder::NextTierDiscount
+            | Placeholder::ProductTitle => true,
+        }
+    }
+
+    fn templates(&self) -> impl Iterator<Item = &MessageTemplate> {
+        [&self.label].into_iter().chain(self.labels.values())
+    }
+}
+
+/// The lowest tier in `tiers` whose threshold is of the same kind as
+/// `tier`'s and higher.
+pub fn next_tier<'a>(tiers: &'a [TierConfig], tier: &TierConfig) -> Option<&'a TierConfig> {
+    tiers
+        .iter()
+        .filter(|other| {
+            same_kind(&other.threshold, &tier.threshold)
+                && other.threshold.sort_key() > tier.threshold.sort_key()
+        })
+        .min_by(|a, b| {
+            a.threshold
+                .sort_key()
+                .partial_cmp(&b.threshold.sort_key())
+                .unwrap_or(Ordering::Equal)
+        })
+}
+
+/// A tier's amounts in one presentment currency, for merchants who set
+/// round local prices instead of converting at the current rate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CurrencyAmounts {
+    /// Replaces the `spend` threshold.
+    pub spend: Option<f64>,
+    /// Replaces the `discount` of a `fixed_amount` tier.
+    pub discount: Option<f64>,
+    /// Replaces the `price` of a `unit_price` tier.
+    pub price: Option<f64>,
+}
+
+// A tier as written in the metafield, before its value is resolved.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTier {
+    qty: Option<i32>,
+    spend: Option<f64>,
+    weight: Option<f64>,
+    #[serde(default)]
+    kind: TierKind,
+    discount: Option<f64>,
+    price: Option<f64>,
+    label: String,
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+    #[serde(default)]
+    currencies: BTreeMap<String, CurrencyAmounts>,
+}
+
+/// Which cart lines pool their quantities toward a tier threshold.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregation {
+    /// Each cart line qualifies on its own quantity.
+    #[default]
+    Line,
+    /// Variants of the same product count together.
+    Product,
+    /// Every product that uses the same volume-discount metaobject counts together.
+    Metaobject,
+    /// Every product priced by the discount's own tiers counts together.
+    /// Only the discount configuration uses this; product metafields can't
+    /// select it.
+    #[serde(skip)]
+    Discount,
+}
+
+/// A validated set of tiers, sorted by ascending threshold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TierSet {
+    /// The GID of the volume-discount metaobject the tiers were copied from.
+    pub id: Option<String>,
+    pub aggregation: Aggregation,
+    pub tiers: Vec<TierConfig>,
+}
+
+/// The field of a tier that failed validation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TierField {
+    Qty,
+    Spend,
+    Weight,
+    Discount,
+    Price,
+    Label,
+    Kind,
+    Currencies,
+}
+
+impl fmt::Display for TierField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TierField::Qty => "qty",
+            TierField::Spend => "spend",
+            TierField::Weight => "weight",
+            TierField::Discount => "discount",
+            TierField::Price => "price",
+            TierField::Label => "label",
+            TierField::Kind => "kind",
+            TierField::Currencies => "currencies",
+        })
+    }
+}
+
+/// Tier sets that replace the tiers of particular products for one kind of
+/// buyer, such as the tiers a distributor's company negotiated or the breaks
+/// of a market.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TierSetOverrides {
+    /// Keyed by the GID of the volume-discount metaobject the replaced tiers
+    /// were copied from, by the GID of a product, or by
+    /// [`DISCOUNT_TIER_SET_KEY`] for the discount's own tiers.
+    pub tier_sets: BTreeMap<String, TierSet>,
+    /// Tier sets for buyers in a particular country, keyed by its ISO 3166-1
+    /// alpha-2 code and then like `tier_sets`. They win over `tier_sets`.
+    pub countries: BTreeMap<String, BTreeMap<String, TierSet>>,
+}
+
+/// The override key of the tiers the discount configuration itself sets, for
+/// products without tiers of their own. GIDs can't collide with it.
+pub const DISCOUNT_TIER_SET_KEY: &str = "discount";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTierSetOverrides {
+    version: u64,
+    #[serde(default)]
+    tier_sets: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    countries: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+}
+
+impl TierSetOverrides {
+    /// Parses and validates `{ "version": 1, "tier_sets": { "<GID>": ... },
+    /// "countries": { "DE": { "<GID>": ... } } }`, where each tier set takes
+    /// any form [`TierSet::parse`] accepts.
+    pub fn parse(json: &str) -> Result<Self, ConfigError> {
+        let config: RawTierSetOverrides =
+            serde_json::from_str(json).map_err(|error| ConfigError::Parse(error.to_string()))?;
+        if config.version != CURRENT_VERSION {
+            return Err(ConfigError::UnsupportedVersion(config.version));
+        }
+
+        Ok(Self {
+            tier_sets: resolve_tier_sets(config.tier_sets, "")?,
+            countries: config
+                .countries
+                .into_iter()
+                .map(|(country, tier_sets)| {
+                    let tier_sets = resolve_tier_sets(tier_sets, &format!("{country}/"))?;
+                    Ok((country, tier_sets))
+                })
+                .collect::<Result<_, ConfigError>>()?,
+        })
+    }
+
+    /// The tier set that replaces the tiers keyed `tier_set_key` on
+    /// `product_id` for buyers in `country`, if any. An override for the
+    /// product wins over one for its metaobject or the discount.
+    pub fn replacement(
+        &self,
+        tier_set_key: Option<&str>,
+        product_id: &str,
+        country: Option<&str>,
+    ) -> Option<&TierSet> {
+        let country_tier_sets = country.and_then(|country| self.countries.get(country));
+        country_tier_sets
+            .into_iter()
+            .chain([&self.tier_sets])
+            .find_map(|tier_sets| {
+                tier_sets
+                    .get(product_id)
+                    .or_else(|| tier_set_key.and_then(|key| tier_sets.get(key)))
+            })
+    }
+}
+
+/// Why a tier configuration was rejected. Tier indexes refer to the
+/// position of the tier in the configuration as the merchant wrote it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigError {
+    /// The configuration isn't valid JSON or doesn't match the schema.
+    Parse(String),
+    /// The configuration declares a schema version this function doesn't know.
+    UnsupportedVersion(u64),
+    /// A tier field holds a value outside its allowed range.
+    InvalidField {
+        tier: usize,
+        field: TierField,
+        reason: String,
+    },
+    /// Two tiers share the same threshold.
+    DuplicateThreshold {
+        tier: usize,
+        other: usize,
+        threshold: Threshold,
+    },
+    /// A tier with a higher threshold is less generous than a lower one with
+    /// the same kinds of threshold and value.
+    NonMonotonicDiscount { tier: usize, previous: usize },
+    /// The targeting rule tests a tag that `rule_tags` doesn't request.
+    UnrequestedTag(String),
+    /// An order tier was rejected. Its tier index refers to `order_tiers`.
+    OrderTiers(Box<ConfigError>),
+    /// A delivery rule's tier was rejected. Its tier index refers to the
+    /// rule's `tiers`.
+    DeliveryRule {
+        rule: usize,
+        error: Box<ConfigError>,
+    },
+    /// A segment tests a customer tag that `customer_tags` doesn't request.
+    UnrequestedCustomerTag(String),
+    /// A customer segment's tier was rejected. Its tier index refers to the
+    /// segment's `tiers`.
+    Segment {
+        segment: usize,
+        error: Box<ConfigError>,
+    },
+    /// One of the tier sets in a set of overrides was rejected.
+    TierSetOverride {
+        key: String,
+        error: Box<ConfigError>,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Parse(message) => write!(f, "invalid tier configuration: {message}"),
+            ConfigError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported tier configuration version {version} (expected {CURRENT_VERSION})"
+            ),
+            ConfigError::InvalidField {
+                tier,
+                field,
+                reason,
+            } => write!(f, "tier {tier}: `{field}` {reason}"),
+            ConfigError::DuplicateThreshold {
+                tier,
+                other,
+                threshold,
+            } => write!(f, "tier {tier}: {threshold} is already used by tier {other}"),
+            ConfigError::NonMonotonicDiscount { tier, previous } => write!(
+                f,
+                "tier {tier}: value is less generous than tier {previous}, which has a lower threshold"
+            ),
+            ConfigError::UnrequestedTag(tag) => {
+                write!(f, "rule tests tag `{tag}`, which isn't listed in `rule_tags`")
+            }
+            ConfigError::OrderTiers(error) => write!(f, "order {error}"),
+            ConfigError::DeliveryRule { rule, error } => {
+                write!(f, "delivery rule {rule}: {error}")
+            }
+            ConfigError::UnrequestedCustomerTag(tag) => write!(
+                f,
+                "segment tests customer tag `{tag}`, which isn't listed in `customer_tags`"
+            ),
+            ConfigError::Segment { segment, error } => write!(f, "segment {segment}: {error}"),
+            ConfigError::TierSetOverride { key, error } => {
+                write!(f, "tier set for `{key}`: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VersionedConfig {
+    version: u64,
+    id: Option<String>,
+    #[serde(default)]
+    aggregation: Aggregation,
+    #[serde(default)]
+    weight_unit: WeightUnit,
+    tiers: Vec<RawTier>,
+}
+
+/// The configuration stored on the discount itself, which decides which
+/// cart lines take part in volume pricing and prices whole collections
+/// instead of individual products.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiscountConfig {
+    /// The collections whose products use `tiers`. When empty, every
+    /// product the rule matches does. The input query receives these as
+    /// `$collection_ids`.
+    pub collection_ids: Vec<String>,
+    /// Lines whose product doesn't match are left out of volume pricing
+    /// altogether.
+    pub rule: Option<Rule>,
+    pub exclude: Exclusions,
+    /// Tiers for products without tiers of their own, shared by every
+    /// product they price.
+    pub tiers: TierSet,
+    /// Tiers on the order subtotal, met by the item count or subtotal of
+    /// every line that isn't excluded. Sorted by ascending threshold.
+    pub order_tiers: Vec<TierConfig>,
+    /// Each delivery option is discounted by the first rule that covers it.
+    pub delivery: Vec<DeliveryRule>,
+    /// Every segment the customer belongs to layers its tiers over the tiers
+    /// that price each product, in order.
+    pub segments: Vec<CustomerSegment>,
+}
+
+/// Percentage tiers for customers with certain tags or order history. Each
+/// tier replaces the base tier with the same threshold, or adds a new break
+/// when there's none.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CustomerSegment {
+    /// The customer has at least one of these tags. Ignored when empty.
+    pub customer_tags: Vec<String>,
+    pub min_number_of_orders: Option<i64>,
+    /// In the shop currency.
+    pub min_amount_spent: Option<f64>,
+    /// Sorted by ascending threshold.
+    pub tiers: Vec<TierConfig>,
+}
+
+impl CustomerSegment {
+    /// Whether `customer` meets every condition of the segment. Segments
+    /// never match anonymous buyers.
+    pub fn matches(&self, customer: Option<&Customer>, presentment_currency_rate: f64) -> bool {
+        let Some(customer) = customer else {
+            return false;
+        };
+        (self.customer_tags.is_empty()
+            || self
+                .customer_tags
+                .iter()
+                .any(|tag| customer.tags.contains(tag)))
+            && self
+                .min_number_of_orders
+                .is_none_or(|orders| customer.number_of_orders >= orders)
+            && self
+                .min_amount_spent
+                .is_none_or(|amount| customer.amount_spent / presentment_currency_rate >= amount)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCustomerSegment {
+    #[serde(default)]
+    customer_tags: Vec<String>,
+    min_number_of_orders: Option<i64>,
+    min_amount_spent: Option<f64>,
+    tiers: Vec<RawTier>,
+}
+
+/// Shipping discounts, met by the quantity of a delivery group's deliverable
+/// lines that aren't excluded.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeliveryRule {
+    /// The delivery methods the rule covers. When empty, it covers every
+    /// method.
+    pub methods: Vec<DeliveryMethod>,
+    /// The delivery options the rule covers. When empty, it covers every
+    /// option.
+    pub handles: Vec<String>,
+    /// Sorted by ascending threshold.
+    pub tiers: Vec<TierConfig>,
+}
+
+impl DeliveryRule {
+    /// Whether the rule covers every option, so it can target whole delivery
+    /// groups.
+    pub fn covers_everything(&self) -> bool {
+        self.methods.is_empty() && self.handles.is_empty()
+    }
+
+    pub fn covers(&self, handle: &str, method: Option<DeliveryMethod>) -> bool {
+        (self.handles.is_empty() || self.handles.iter().any(|covered| covered == handle))
+            && (self.methods.is_empty()
+                || method.is_some_and(|method| self.methods.contains(&method)))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawDeliveryRule {
+    #[serde(default)]
+    methods: Vec<DeliveryMethod>,
+    #[serde(default)]
+    handles: Vec<String>,
+    #[serde(default)]
+    tiers: Vec<RawTier>,
+}
+
+// `delivery` was a single rule before rules could be scoped to delivery
+// methods.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawDeliveryRules {
+    One(RawDeliveryRule),
+    Many(Vec<RawDeliveryRule>),
+}
+
+impl Default for RawDeliveryRules {
+    fn default() -> Self {
+        RawDeliveryRules::Many(vec![])
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawDiscountConfig {
+    version: u64,
+    #[serde(default)]
+    collection_ids: Vec<String>,
+    rule: Option<Rule>,
+    // Passed to the input query as `$rule_tags`, since it can only report
+    // on the tags it's asked about.
+    #[serde(default)]
+    rule_tags: Vec<String>,
+    #[serde(default)]
+    exclude: Exclusions,
+    #[serde(default)]
+    weight_unit: WeightUnit,
+    #[serde(default)]
+    tiers: Vec<RawTier>,
+    #[serde(default)]
+    order_tiers: Vec<RawTier>,
+    #[serde(default)]
+    delivery: RawDeliveryRules,
+    // Passed to the input query as `$customer_tags`, like `rule_tags`.
+    #[serde(default)]
+    customer_tags: Vec<String>,
+    #[serde(default)]
+    segments: Vec<RawCustomerSegment>,
+}
+
+impl DiscountConfig {
+    /// Parses and validates `{ "version": 1, "collection_ids": [...],
+    /// "rule": {...}, "rule_tags": [...], "exclude": {...}, "tiers": [...],
+    /// "order_tiers": [...], "delivery": [{ "methods": [...], "handles": [...],
+    /// "tiers": [...] }], "customer_tags": [...], "segments": [{
+    /// "customer_tags": [...], "min_number_of_orders": 5,
+    /// "min_amount_spent": 1000.0, "tiers": [...] }] }`.
+    pub fn parse(json: &str) -> Result<Self, ConfigError> {
+        let config: RawDiscountConfig =
+            serde_json::from_str(json).map_err(|error| ConfigError::Parse(error.to_string()))?;
+        if config.version != CURRENT_VERSION {
+            return Err(ConfigError::UnsupportedVersion(config.version));
+        }
+        if let Some(rule) = &config.rule {
+            if let Some(tag) = rule
+                .tags()
+                .into_iter()
+                .find(|tag| !config.rule_tags.iter().any(|requested| requested == tag))
+            {
+                return Err(ConfigError::UnrequestedTag(tag.to_string()));
+            }
+        }
+        if let Some(tag) = config
+            .segments
+            .iter()
+            .flat_map(|segment| &segment.customer_tags)
+            .find(|tag| !config.customer_tags.contains(tag))
+        {
+            return Err(ConfigError::UnrequestedCustomerTag(tag.clone()));
+        }
+
+        Ok(Self {
+            collection_ids: config.collection_ids,
+            rule: config.rule,
+            exclude: config.exclude,
+            tiers: TierSet {
+                id: None,
+                aggregation: Aggregation::Discount,
+                tiers: resolve_tiers(config.tiers, config.weight_unit, &[])?,
+            },
+            order_tiers: resolve_order_tiers(config.order_tiers)
+                .map_err(|error| ConfigError::OrderTiers(Box::new(error)))?,
+            delivery: resolve_delivery_rules(config.delivery)?,
+            segments: resolve_segments(config.segments, config.weight_unit)?,
+        })
+    }
+
+    /// Parses the cart's discount configuration, if it has one.
+    pub fn from_cart(cart: &Cart) -> Option<Result<Self, ConfigError>> {
+        cart.discount_config.as_deref().map(Self::parse)
+    }
+
+    /// Whether `merchandise` takes part in volume pricing at all.
+    pub fn includes(&self, merchandise: &Merchandise) -> bool {
+        let Merchandise::Variant(variant) = merchandise else {
+            return false;
+        };
+        !self.exclude.excludes(merchandise)
+            && self
+                .rule
+                .as_ref()
+                .is_none_or(|rule| rule.matches(&variant.product))
+    }
+
+    /// Whether `product` is priced by the discount's own tiers when it has
+    /// none of its own.
+    pub fn targets(&self, product: &Product) -> bool {
+        self.collection_ids.is_empty() || product.in_discount_collections
+    }
+}
+
+impl TierSet {
+    /// Parses and validates a tier configuration.
+    ///
+    /// Accepts either `{ "version": 1, "tiers": [...] }` or, for metafields
+    /// written before the schema was versioned, a bare array of tiers that
+    /// are evaluated per line.
+    pub fn parse(json: &str) -> Result<Self, ConfigError> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|error| ConfigError::Parse(error.to_string()))?;
+        Self::from_value(value)
+    }
+
+    fn from_value(value: serde_json::Value) -> Result<Self, ConfigError> {
+        let config = if value.is_array() {
+            VersionedConfig {
+                version: CURRENT_VERSION,
+                id: None,
+                aggregation: Aggregation::Line,
+                weight_unit: WeightUnit::Kilograms,
+                tiers: serde_json::from_value(value)
+                    .map_err(|error| ConfigError::Parse(error.to_string()))?,
+            }
+        } else {
+            serde_json::from_value(value).map_err(|error| ConfigError::Parse(error.to_string()))?
+        };
+        if config.version != CURRENT_VERSION {
+            return Err(ConfigError::UnsupportedVersion(config.version));
+        }
+
+        Ok(Self {
+            id: config.id,
+            aggregation: config.aggregation,
+            tiers: resolve_tiers(config.tiers, config.weight_unit, &[])?,
+        })
+    }
+
+    /// A copy with `tiers` replacing the tiers that share their threshold and
+    /// added alongside the rest. Tiers worth less than a lower tier they're
+    /// comparable with are dropped, so more volume still never lowers the
+    /// discount.
+    pub fn layered(&self, tiers: &[TierConfig]) -> TierSet {
+        let mut layered = self.clone();
+        for tier in tiers {
+            match layered
+                .tiers
+                .iter_mut()
+                .find(|base| base.threshold == tier.threshold)
+            {
+                Some(base) => *base = tier.clone(),
+                None => layered.tiers.push(tier.clone()),
+            }
+        }
+
+        let mut kept: Vec<TierConfig> = vec![];
+        for tier in sorted(layered.tiers) {
+            let comparable = kept.iter().rev().find(|lower| {
+                same_kind(&lower.threshold, &tier.threshold) && same_kind(&lower.value, &tier.value)
+            });
+            if comparable.is_none_or(|lower| tier.value.is_at_least(&lower.value)) {
+                kept.push(tier);
+            }
+        }
+        layered.tiers = kept;
+        layered
+    }
+
+    /// The tier set with its amounts in `currency`.
+    pub fn in_currency(&self, currency: &Currency) -> TierSet {
+        TierSet {
+            id: self.id.clone(),
+            aggregation: self.aggregation,
+            tiers: sorted(
+                self.tiers
+                    .iter()
+                    .map(|tier| tier.in_currency(currency))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The met tier with the highest threshold of each kind. Tiers with
+    /// different kinds of threshold can qualify together; which one is worth
+    /// more depends on the line's price.
+    pub fn qualifying_tiers(&self, volume: &Volume) -> Vec<&TierConfig> {
+        let mut qualifying: Vec<&TierConfig> = vec![];
+        for tier in self
+            .tiers
+            .iter()
+            .filter(|tier| tier.threshold.is_met_by(volume))
+        {
+            match qualifying.last_mut() {
+                Some(last) if same_kind(&last.threshold, &tier.threshold) => *last = tier,
+                _ => qualifying.push(tier),
+            }
+        }
+        qualifying
+    }
+
+    /// The lowest tier of `current`'s threshold kind above it, or without a
+    /// current tier, the lowest tier `volume` doesn't meet yet.
+    pub fn next_tier(&self, volume: &Volume, current: Option<&TierConfig>) -> Option<&TierConfig> {
+        match current {
+            Some(current) => next_tier(&self.tiers, current),
+            None => self
+                .tiers
+                .iter()
+                .find(|tier| !tier.threshold.is_met_by(volume)),
+        }
+    }
+}
+
+/// Validates `raw_tiers` and sorts them by threshold. Their messages can't
+/// use the `unavailable` placeholders.
+fn resolve_tiers(
+    raw_tiers: Vec<RawTier>,
+    weight_unit: WeightUnit,
+    unavailable: &[Placeholder],
+) -> Result<Vec<TierConfig>, ConfigError> {
+    let tiers = raw_tiers
+        .into_iter()
+        .enumerate()
+        .map(|(index, tier)| resolve_tier(index, tier, weight_unit, unavailable))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (index, tier) in tiers.iter().enumerate() {
+        let next_tier_placeholder = tier
+            .templates()
+            .flat_map(MessageTemplate::placeholders)
+            .find(|placeholder| {
+                matches!(
+                    placeholder,
+                    Placeholder::NextTierQty
+                        | Placeholder::NextTierRemaining
+                        | Placeholder::NextTierDiscount
+                )
+            });
+        if let Some(placeholder) = next_tier_placeholder {
+            if next_tier(&tiers, tier).is_none() {
+                return Err(ConfigError::InvalidField {
+                    tier: index,
+                    field: TierField::Label,
+                    reason: format!(
+                        "uses `{placeholder}`, but no tier has a higher `{}`",
+                        tier.threshold.field()
+                    ),
+                });
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..tiers.len()).collect();
+    order.sort_by(|&a, &b| {
+        tiers[a]
+            .threshold
+            .sort_key()
+            .partial_cmp(&tiers[b].threshold.sort_key())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    for (position, pair) in order.windows(2).enumerate() {
+        let (previous, tier) = (pair[0], pair[1]);
+        if tiers[previous].threshold == tiers[tier].threshold {
+            return Err(ConfigError::DuplicateThreshold {
+                tier: tier.max(previous),
+                other: tier.min(previous),
+                threshold: tiers[tier].threshold,
+            });
+        }
+
+        let comparable = order[..=position].iter().rev().find(|&&index| {
+            same_kind(&tiers[index].threshold, &tiers[tier].threshold)
+                && same_kind(&tiers[index].value, &tiers[tier].value)
+        });
+        if let Some(&previous) = comparable {
+            if !tiers[tier].value.is_at_least(&tiers[previous].value) {
+                return Err(ConfigError::NonMonotonicDiscount { tier, previous });
+            }
+        }
+    }
+
+    Ok(sorted(tiers))
+}
+
+fn sorted(mut tiers: Vec<TierConfig>) -> Vec<TierConfig> {
+    tiers.sort_by(|a, b| {
+        a.threshold
+            .sort_key()
+            .partial_cmp(&b.threshold.sort_key())
+            .unwrap_or(Ordering::Equal)
+    });
+    tiers
+}
+
+// Order discounts can only be conditioned on an item count or a subtotal, and
+// have no unit price to set.
+fn resolve_order_tiers(raw_tiers: Vec<RawTier>) -> Result<Vec<TierConfig>, ConfigError> {
+    reject_unsupported(&raw_tiers, "order", |tier| {
+        if tier.weight.is_some() {
+            Some(TierField::Weight)
+        } else if tier.kind == TierKind::UnitPrice {
+            Some(TierField::Kind)
+        } else {
+            None
+        }
+    })?;
+    resolve_tiers(
+        raw_tiers,
+        WeightUnit::Kilograms,
+        &[Placeholder::ProductTitle],
+    )
+}
+
+fn resolve_delivery_rules(raw_rules: RawDeliveryRules) -> Result<Vec<DeliveryRule>, ConfigError> {
+    let raw_rules = match raw_rules {
+        RawDeliveryRules::One(raw_rule) => vec![raw_rule],
+        RawDeliveryRules::Many(raw_rules) => raw_rules,
+    };
+
+    raw_rules
+        .into_iter()
+        .enumerate()
+        .map(|(index, raw_rule)| {
+            Ok(DeliveryRule {
+                methods: raw_rule.methods,
+                handles: raw_rule.handles,
+                tiers: resolve_delivery_tiers(raw_rule.tiers).map_err(|error| {
+                    ConfigError::DeliveryRule {
+                        rule: index,
+                        error: Box::new(error),
+                    }
+                })?,
+            })
+        })
+        .collect()
+}
+
+// Keys in errors are prefixed with `key_prefix`, so they point at the right
+// map.
+fn resolve_tier_sets(
+    raw_tier_sets: BTreeMap<String, serde_json::Value>,
+    key_prefix: &str,
+) -> Result<BTreeMap<String, TierSet>, ConfigError> {
+    raw_tier_sets
+        .into_iter()
+        .map(|(key, value)| match TierSet::from_value(value) {
+            Ok(tier_set) => Ok((key, tier_set)),
+            Err(error) => Err(ConfigError::TierSetOverride {
+                key: format!("{key_prefix}{key}"),
+                error: Box::new(error),
+            }),
+        })
+        .collect()
+}
+
+// Segments override discount percentages, so they have no other kind of value.
+fn resolve_segments(
+    raw_segments: Vec<RawCustomerSegment>,
+    weight_unit: WeightUnit,
+) -> Result<Vec<CustomerSegment>, ConfigError> {
+    raw_segments
+        .into_iter()
+        .enumerate()
+        .map(|(index, raw_segment)| {
+            let tiers = reject_unsupported(&raw_segment.tiers, "segment", |tier| {
+                (tier.kind != TierKind::Percentage).then_some(TierField::Kind)
+            })
+            .and_then(|()| resolve_tiers(raw_segment.tiers, weight_unit, &[]))
+            .map_err(|error| ConfigError::Segment {
+                segment: index,
+                error: Box::new(error),
+            })?;
+            Ok(CustomerSegment {
+                customer_tags: raw_segment.customer_tags,
+                min_number_of_orders: raw_segment.min_number_of_orders,
+                min_amount_spent: raw_segment.min_amount_spent,
+                tiers,
+            })
+        })
+        .collect()
+}
+
+// The delivery export only sees the quantities of deliverable lines.
+fn resolve_delivery_tiers(raw_tiers: Vec<RawTier>) -> Result<Vec<TierConfig>, ConfigError> {
+    reject_unsupported(&raw_tiers, "delivery", |tier| {
+        if tier.spend.is_some() {
+            Some(TierField::Spend)
+        } else if tier.weight.is_some() {
+            Some(TierField::Weight)
+        } else if tier.kind == TierKind::UnitPrice {
+            Some(TierField::Kind)
+        } else {
+            None
+        }
+    })?;
+    resolve_tiers(
+        raw_tiers,
+        WeightUnit::Kilograms,
+        &[Placeholder::ProductTitle],
+    )
+}
+
+fn reject_unsupported(
+    raw_tiers: &[RawTier],
+    tiers_name: &str,
+    unsupported_field: impl Fn(&RawTier) -> Option<TierField>,
+) -> Result<(), ConfigError> {
+    for (index, tier) in raw_tiers.iter().enumerate() {
+        if let Some(field) = unsupported_field(tier) {
+            return Err(ConfigError::InvalidField {
+                tier: index,
+                field,
+                reason: format!("isn't supported by {tiers_name} tiers"),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn same_kind<T>(a: &T, b: &T) -> bool {
+    mem::discriminant(a) == mem::discriminant(b)
+}
+
+fn resolve_tier(
+    index: usize,
+    tier: RawTier,
+    weight_unit: WeightUnit,
+    unavailable: &[Placeholder],
+) -> Result<TierConfig, ConfigError> {
+    let invalid = |field, reason: String| ConfigError::InvalidField {
+        tier: index,
+        field,
+        reason,
+    };
+
+    let mut thresholds = [
+        tier.qty
+            .map(|qty| (TierField::Qty, f64::from(qty), Threshold::Quantity(qty))),
+        tier.spend
+            .map(|spend| (TierField::Spend, spend, Threshold::Subtotal(spend))),
+        tier.weight.map(|weight| {
+            let kilograms = weight_unit.to_kilograms(weight);
+            (TierField::Weight, weight, Threshold::Weight(kilograms))
+        }),
+    ]
+    .into_iter()
+    .flatten();
+    let Some((field, amount, threshold)) = thresholds.next() else {
+        return Err(invalid(
+            TierField::Qty,
+            "or `spend` or `weight` is required".to_string(),
+        ));
+    };
+    if let Some((other, _, _)) = thresholds.next() {
+        return Err(invalid(other, format!("can't be combined with `{field}`")));
+    }
+    if !amount.is_finite() || amount < 0.0 {
+        return Err(invalid(
+            field,
+            format!("must not be negative, got {amount}"),
+        ));
+    }
+    if tier.label.trim().is_empty() {
+        return Err(invalid(TierField::Label, "must not be empty".to_string()));
+    }
+    if let Some(language) = tier
+        .labels
+        .iter()
+        .find_map(|(language, label)| label.trim().is_empty().then_some(language))
+    {
+        return Err(invalid(
+            TierField::Label,
+            format!("for `{language}` must not be empty"),
+        ));
+    }
+
+    let (field, amount, unused_field, unused) = match tier.kind {
+        TierKind::Percentage | TierKind::FixedAmount => (
+            TierField::Discount,
+            tier.discount,
+            TierField::Price,
+            tier.price,
+        ),
+        TierKind::UnitPrice => (
+            TierField::Price,
+            tier.price,
+            TierField::Discount,
+            tier.discount,
+        ),
+    };
+    if unused.is_some() {
+        return Err(invalid(
+            unused_field,
+            format!("isn't used by `{}` tiers", tier.kind),
+        ));
+    }
+    let Some(amount) = amount else {
+        return Err(invalid(
+            field,
+            format!("is required for `{}` tiers", tier.kind),
+        ));
+    };
+    if !amount.is_finite() || amount < 0.0 {
+        return Err(invalid(
+            field,
+            format!("must not be negative, got {amount}"),
+        ));
+    }
+
+    let value = match tier.kind {
+        TierKind::Percentage => {
+            if amount > 100.0 {
+                return Err(invalid(
+                    field,
+                    format!("must be between 0 and 100, got {amount}"),
+                ));
+            }
+            TierValue::Percentage(amount)
+        }
+        TierKind::FixedAmount => TierValue::FixedAmount(amount),
+        TierKind::UnitPrice => TierValue::UnitPrice(amount),
+    };
+
+    for (code, amounts) in &tier.currencies {
+        let overrides = [
+            (
+                "spend",
+                amounts.spend,
+                matches!(threshold, Threshold::Subtotal(_)),
+            ),
+            (
+                "discount",
+                amounts.discount,
+                matches!(value, TierValue::FixedAmount(_)),
+            ),
+            (
+                "price",
+                amounts.price,
+                matches!(value, TierValue::UnitPrice(_)),
+            ),
+        ];
+        for (name, amount, used) in overrides {
+            let Some(amount) = amount else {
+                continue;
+            };
+            if !used {
+                return Err(invalid(
+                    TierField::Currencies,
+                    format!("`{code}` sets `{name}`, which the tier doesn't use"),
+                ));
+            }
+            if !amount.is_finite() || amount < 0.0 {
+                return Err(invalid(
+                    TierField::Currencies,
+                    format!("`{code}` `{name}` must not be negative, got {amount}"),
+                ));
+            }
+        }
+    }
+
+    let label = MessageTemplate::parse(&tier.label)
+        .map_err(|error| invalid(TierField::Label, error.to_string()))?;
+    let mut labels = BTreeMap::new();
+    for (written, label) in tier.labels {
+        // Merchants may write `pt-BR` for Shopify's `PT_BR`.
+        let language = written.to_ascii_uppercase().replace('-', "_");
+        if labels.contains_key(&language) {
+            return Err(invalid(
+                TierField::Label,
+                format!("for `{written}` repeats another translation for `{language}`"),
+            ));
+        }
+        let label = MessageTemplate::parse(&label)
+            .map_err(|error| invalid(TierField::Label, format!("for `{language}` {error}")))?;
+        labels.insert(language, label);
+    }
+    let tier = TierConfig {
+        threshold,
+        value,
+        label,
+        labels,
+        currencies: tier.currencies,
+    };
+
+    for placeholder in tier.templates().flat_map(MessageTemplate::placeholders) {
+        if !tier.fills(placeholder) {
+            return Err(invalid(
+                TierField::Label,
+                format!("uses `{placeholder}`, which the tier doesn't have"),
+            ));
+        }
+        if unavailable.contains(&placeholder) {
+            return Err(invalid(
+                TierField::Label,
+                format!("uses `{placeholder}`, which isn't available in these messages"),
+            ));
+        }
+    }
+    Ok(tier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(tiers: serde_json::Value) -> Result<TierSet, ConfigError> {
+        TierSet::parse(&json!({ "version": 1, "tiers": tiers }).to_string())
+    }
+
+    #[test]
+    fn rejects_a_negative_qty() {
+        assert_eq!(
+            parse(json!([
+                { "qty": 5, "discount": 5.0, "label": "5+" },
+                { "qty": -1, "discount": 10.0, "label": "-1+" },
+            ])),
+            Err(ConfigError::InvalidField {
+                tier: 1,
+                field: TierField::Qty,
+                reason: "must not be negative, got -1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_a_percentage_above_100() {
+        assert_eq!(
+            parse(json!([{ "qty": 5, "discount": 150.0, "label": "5+" }])),
+            Err(ConfigError::InvalidField {
+                tier: 0,
+                field: TierField::Discount,
+                reason: "must be between 0 and 100, got 150".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_an_unsupported_version() {
+        assert_eq!(
+            TierSet::parse(&json!({ "version": 2, "tiers": [] }).to_string()),
+            Err(ConfigError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn reports_tiers_by_their_position_as_written() {
+        // Sorted by threshold, the invalid tier would be the second.
+        assert_eq!(
+            parse(json!([
+                { "qty": 50, "discount": 20.0, "label": "50+" },
+                { "qty": 5, "discount": 5.0, "label": "5+" },
+                { "qty": 10, "discount": 150.0, "label": "10+" },
+            ])),
+            Err(ConfigError::InvalidField {
+                tier: 2,
+                field: TierField::Discount,
+                reason: "must be between 0 and 100, got 150".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_a_higher_tier_that_gives_less() {
+        assert_eq!(
+            parse(json!([
+                { "qty": 50, "discount": 5.0, "label": "50+" },
+                { "qty": 5, "discount": 2.0, "label": "5+" },
+                { "qty": 10, "discount": 10.0, "label": "10+" },
+            ])),
+            Err(ConfigError::NonMonotonicDiscount {
+                tier: 0,
+                previous: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_translations_for_the_same_language() {
+        assert_eq!(
+            parse(json!([{
+                "qty": 5,
+                "discount": 5.0,
+                "label": "Buy 5+",
+                "labels": { "PT_BR": "Compre 5+", "pt-BR": "Leve 5+" },
+            }])),
+            Err(ConfigError::InvalidField {
+                tier: 0,
+                field: TierField::Label,
+                reason: "for `pt-BR` repeats another translation for `PT_BR`".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_a_duplicate_threshold() {
+        assert_eq!(
+            parse(json!([
+                { "qty": 10, "discount": 5.0, "label": "10+" },
+                { "qty": 5, "discount": 2.0, "label": "5+" },
+                { "qty": 10, "discount": 8.0, "label": "10+" },
+            ])),
+            Err(ConfigError::DuplicateThreshold {
+                tier: 2,
+                other: 0,
+                threshold: Threshold::Quantity(10),
+            })
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use crate::cart::Cart;
+
+// ISO 4217 currencies without minor units, or with three decimal places.
+// Every other currency has two.
+const ZERO_DECIMAL_CURRENCIES: [&str; 17] = [
+    "BIF", "CLP", "DJF", "GNF", "ISK", "JPY", "KMF", "KRW", "PYG", "RWF", "UGX", "UYI", "VND",
+    "VUV", "XAF", "XOF", "XPF",
+];
+const THREE_DECIMAL_CURRENCIES: [&str; 7] = ["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+
+/// The buyer's presentment currency, which every amount the engine hands back
+/// is in. Tiers are configured in the shop currency.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Currency<'a> {
+    /// The ISO 4217 code, when known.
+    pub code: Option<&'a str>,
+    /// Multiply a shop-currency amount by this to get the presentment amount.
+    pub rate: f64,
+}
+
+impl<'a> Currency<'a> {
+    pub fn of(cart: &'a Cart) -> Self {
+        Self {
+            code: cart.currency_code.as_deref(),
+            rate: cart.presentment_currency_rate,
+        }
+    }
+
+    /// The number of decimal places amounts are rounded to.
+    pub fn decimals(&self) -> i32 {
+        match self.code {
+            Some(code) if ZERO_DECIMAL_CURRENCIES.contains(&code) => 0,
+            Some(code) if THREE_DECIMAL_CURRENCIES.contains(&code) => 3,
+            _ => 2,
+        }
+    }
+
+    pub fn round(&self, amount: f64) -> f64 {
+        let scale = 10f64.powi(self.decimals());
+        (amount * scale).round() / scale
+    }
+
+    /// `explicit` when the merchant set an amount for this currency, and
+    /// otherwise `shop_amount` converted at the presentment rate.
+    pub fn convert(&self, shop_amount: f64, explicit: Option<f64>) -> f64 {
+        self.round(explicit.unwrap_or(shop_amount * self.rate))
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::ConfigError;
+use crate::config::Threshold;
+use crate::config::TierValue;
+use serde::Serialize;
+
+/// How much a discount takes off its target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiscountValue {
+    Percentage(f64),
+    /// An amount in the presentment currency, taken once from the target or,
+    /// with `applies_to_each_item`, from each of its units.
+    FixedAmount {
+        amount: f64,
+        applies_to_each_item: bool,
+    },
+}
+
+/// A discount on a single cart line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineDiscount {
+    pub line_id: String,
+    pub value: DiscountValue,
+    pub message: String,
+}
+
+/// The closest tier a cart line hasn't reached yet, for upsell hints such as
+/// "Add 3 more to save 15%". Serializes to JSON a theme can read from a cart
+/// attribute, e.g.
+/// `{"line_id":"gid://shopify/CartLine/1","threshold":{"qty":10},"remaining":{"qty":3},"value":{"percentage":15.0}}`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct NextTier {
+    pub line_id: String,
+    /// The next tier's threshold, with spend in the presentment currency.
+    pub threshold: Threshold,
+    /// How far the line's group is from `threshold`.
+    pub remaining: Threshold,
+    /// What the next tier offers, in the presentment currency.
+    pub value: TierValue,
+}
+
+/// What the engine decided for the product discount class.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProductDecision {
+    pub discounts: Vec<LineDiscount>,
+    /// The next tier of every priced line that has one, whether or not a
+    /// tier applies to it yet.
+    pub next_tiers: Vec<NextTier>,
+    /// Products whose tier configuration was rejected, and why.
+    pub config_errors: Vec<(String, ConfigError)>,
+    /// Companies, company locations and markets whose tier set overrides
+    /// were rejected, and why. Their buyers get the tiers they'd otherwise
+    /// have.
+    pub override_errors: Vec<(String, ConfigError)>,
+}
+
+impl ProductDecision {
+    /// `next_tiers` as a JSON array, to store in a cart attribute.
+    pub fn next_tiers_json(&self) -> String {
+        serde_json::to_string(&self.next_tiers).unwrap_or_else(|_| "[]".to_string())
+    }
+}
+
+/// A discount on the order subtotal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderDiscount {
+    pub value: DiscountValue,
+    pub message: String,
+    pub excluded_line_ids: Vec<String>,
+    /// What Shopify checks again before it applies the discount.
+    pub conditions: Vec<OrderCondition>,
+}
+
+/// A threshold an order discount is conditioned on.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OrderCondition {
+    /// The order subtotal, in the shop currency, without the excluded lines.
+    MinimumSubtotal {
+        amount: f64,
+        excluded_line_ids: Vec<String>,
+    },
+    /// The combined quantity of the lines.
+    MinimumQuantity {
+        quantity: i32,
+        line_ids: Vec<String>,
+    },
+}
+
+/// A discount on shipping.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeliveryDiscount {
+    pub target: DeliveryTarget,
+    pub value: DiscountValue,
+    pub message: String,
+}
+
+/// What a delivery discount applies to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeliveryTarget {
+    /// Every option of the delivery group with this ID.
+    Group(String),
+    /// The delivery option with this handle.
+    Option(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_tiers_serialize_for_a_cart_attribute() {
+        let decision = ProductDecision {
+            next_tiers: vec![
+                NextTier {
+                    line_id: "gid://shopify/CartLine/1".to_string(),
+                    threshold: Threshold::Quantity(10),
+                    remaining: Threshold::Quantity(3),
+                    value: TierValue::Percentage(15.0),
+                },
+                NextTier {
+                    line_id: "gid://shopify/CartLine/2".to_string(),
+                    threshold: Threshold::Subtotal(100.0),
+                    remaining: Threshold::Subtotal(40.0),
+                    value: TierValue::FixedAmount(2.5),
+                },
+            ],
+            ..ProductDecision::default()
+        };
+        assert_eq!(
+            decision.next_tiers_json(),
+            concat!(
+                r#"[{"line_id":"gid://shopify/CartLine/1","threshold":{"qty":10},"#,
+                r#""remaining":{"qty":3},"value":{"percentage":15.0}},"#,
+                r#"{"line_id":"gid://shopify/CartLine/2","threshold":{"spend":100.0},"#,
+                r#""remaining":{"spend":40.0},"value":{"fixed_amount":2.5}}]"#,
+            )
+        );
+    }
+}
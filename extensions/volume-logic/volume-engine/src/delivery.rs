@@ -0,0 +1,140 @@
+use crate::cart::Cart;
+use crate::cart::DeliveryGroup;
+use crate::config::next_tier;
+use crate::config::ConfigError;
+use crate::config::DiscountConfig;
+use crate::config::TierConfig;
+use crate::config::TierValue;
+use crate::config::Volume;
+use crate::currency::Currency;
+use crate::decision::DeliveryDiscount;
+use crate::decision::DeliveryTarget;
+use crate::decision::DiscountValue;
+use crate::message::MessageValues;
+
+/// The discounts for the shipping discount class. Each delivery group is
+/// evaluated on its own deliverable lines. Fails when the discount
+/// configuration is invalid.
+pub fn delivery_discounts(cart: &Cart) -> Result<Vec<DeliveryDiscount>, ConfigError> {
+    let Some(discount_config) = DiscountConfig::from_cart(cart).transpose()? else {
+        return Ok(vec![]);
+    };
+
+    Ok(cart
+        .delivery_groups
+        .iter()
+        .flat_map(|delivery_group| group_discounts(cart, &discount_config, delivery_group))
+        .collect())
+}
+
+// Each option of the group gets the highest tier its group meets from the
+// first rule that covers it. Fixed amounts are capped at each option's cost,
+// so they always target options.
+fn group_discounts(
+    cart: &Cart,
+    discount_config: &DiscountConfig,
+    delivery_group: &DeliveryGroup,
+) -> Vec<DeliveryDiscount> {
+    let volume = Volume {
+        quantity: cart
+            .deliverable_lines
+            .iter()
+            .filter(|line| delivery_group.line_ids.contains(&line.id))
+            .filter(|line| !discount_config.exclude.excludes(&line.merchandise))
+            .map(|line| line.quantity)
+            .sum(),
+        ..Volume::default()
+    };
+
+    let mut discounts = vec![];
+    let mut covered = vec![false; delivery_group.options.len()];
+    for rule in &discount_config.delivery {
+        let newly_covered: Vec<usize> = delivery_group
+            .options
+            .iter()
+            .enumerate()
+            .filter(|(index, option)| {
+                !covered[*index] && rule.covers(&option.handle, option.method)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if newly_covered.is_empty() {
+            continue;
+        }
+        let whole_group = rule.covers_everything() && newly_covered.len() == covered.len();
+        for &index in &newly_covered {
+            covered[index] = true;
+        }
+
+        let currency = Currency::of(cart);
+        let tiers: Vec<TierConfig> = rule
+            .tiers
+            .iter()
+            .map(|tier| tier.in_currency(&currency))
+            .collect();
+        let Some(tier) = tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.threshold.is_met_by(&volume))
+        else {
+            continue;
+        };
+        let discount = |target, value, saved_amount: f64| DeliveryDiscount {
+            target,
+            value,
+            message: tier.message(
+                cart.language.as_deref(),
+                next_tier(&tiers, tier),
+                &volume,
+                MessageValues {
+                    saved_amount: Some(currency.round(saved_amount)),
+                    currency_decimals: currency.decimals(),
+                    ..MessageValues::default()
+                },
+            ),
+        };
+        match tier.value {
+            // The buyer saves at least as much as on the cheapest option.
+            TierValue::Percentage(percentage) if whole_group => discounts.push(discount(
+                DeliveryTarget::Group(delivery_group.id.clone()),
+                DiscountValue::Percentage(percentage),
+                delivery_group
+                    .options
+                    .iter()
+                    .map(|option| option.cost)
+                    .fold(f64::INFINITY, f64::min)
+                    * percentage
+                    / 100.0,
+            )),
+            TierValue::Percentage(percentage) => {
+                discounts.extend(newly_covered.iter().map(|&index| {
+                    let option = &delivery_group.options[index];
+                    discount(
+                        DeliveryTarget::Option(option.handle.clone()),
+                        DiscountValue::Percentage(percentage),
+                        option.cost * percentage / 100.0,
+                    )
+                }))
+            }
+            TierValue::FixedAmount(amount) => {
+                discounts.extend(newly_covered.iter().filter_map(|&index| {
+                    let option = &delivery_group.options[index];
+                    let amount = currency.round(amount.min(option.cost));
+                    (amount > 0.0).then(|| {
+                        discount(
+                            DeliveryTarget::Option(option.handle.clone()),
+                            DiscountValue::FixedAmount {
+                                amount,
+                                applies_to_each_item: false,
+                            },
+                            amount,
+                        )
+                    })
+                }))
+            }
+            // Rejected when the configuration is parsed.
+            TierValue::UnitPrice(_) => {}
+        }
+    }
+    discounts
+}
@@ -0,0 +1,42 @@
+use crate::cart::Merchandise;
+use serde::Deserialize;
+
+/// Merchandise that never receives volume pricing or counts toward a
+/// threshold, such as clearance items and gift cards. Order discounts leave
+/// excluded lines out of the subtotal they apply to.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Exclusions {
+    #[serde(default)]
+    pub variant_ids: Vec<String>,
+    /// Case-sensitive, like SKUs themselves.
+    #[serde(default)]
+    pub sku_prefixes: Vec<String>,
+    #[serde(default)]
+    pub gift_cards: bool,
+    /// Excludes merchandise whose `requiresShipping` equals this, when set.
+    pub requires_shipping: Option<bool>,
+}
+
+impl Exclusions {
+    pub fn excludes(&self, merchandise: &Merchandise) -> bool {
+        let (is_gift_card, requires_shipping) = match merchandise {
+            Merchandise::Variant(variant) => {
+                if self.variant_ids.contains(&variant.id)
+                    || variant.sku.as_deref().is_some_and(|sku| {
+                        self.sku_prefixes
+                            .iter()
+                            .any(|prefix| sku.starts_with(prefix.as_str()))
+                    })
+                {
+                    return true;
+                }
+                (variant.product.is_gift_card, variant.requires_shipping)
+            }
+            Merchandise::Custom(custom) => (custom.is_gift_card, custom.requires_shipping),
+            Merchandise::Other => return false,
+        };
+
+        (self.gift_cards && is_gift_card) || self.requires_shipping == Some(requires_shipping)
+    }
+}
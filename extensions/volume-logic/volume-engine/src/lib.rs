@@ -0,0 +1,17 @@
+//! Volume pricing rules, independent of the Shopify Function runtime.
+//!
+//! The `volume-logic` extension maps its generated `schema` input types onto
+//! the plain types in [`cart`], asks the engine for a decision, and maps the
+//! decision back onto Function operations. Everything in between lives here,
+//! so it can be reused and tested natively.
+
+pub mod cart;
+pub mod config;
+pub mod currency;
+pub mod decision;
+pub mod delivery;
+pub mod exclusion;
+pub mod message;
+pub mod order;
+pub mod product;
+pub mod rule;
@@ -0,0 +1,404 @@
+use std::fmt;
+
+/// A value a discount message can include, written `{name}` in a label.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placeholder {
+    /// The tier's `qty` threshold. `{qty}` is accepted too.
+    TierQty,
+    /// The tier's `spend` threshold.
+    Spend,
+    /// What the tier offers: a percentage such as `10%`, a fixed amount or a
+    /// unit price.
+    Discount,
+    /// A `percentage` tier's discount, without the percent sign.
+    Percent,
+    /// A `fixed_amount` tier's discount.
+    Amount,
+    /// A `unit_price` tier's price.
+    Price,
+    /// How much the discount takes off what it targets.
+    SavedAmount,
+    /// The `qty` threshold of the next tier up.
+    NextTierQty,
+    /// How many more units, or how much more spend, the next tier up needs.
+    NextTierRemaining,
+    /// What the next tier up offers, written like `{discount}`.
+    NextTierDiscount,
+    /// The title of the discounted line's product.
+    ProductTitle,
+}
+
+impl Placeholder {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "tier_qty" | "qty" => Placeholder::TierQty,
+            "spend" => Placeholder::Spend,
+            "discount" => Placeholder::Discount,
+            "percent" => Placeholder::Percent,
+            "amount" => Placeholder::Amount,
+            "price" => Placeholder::Price,
+            "saved_amount" => Placeholder::SavedAmount,
+            "next_tier_qty" => Placeholder::NextTierQty,
+            "next_tier_remaining" => Placeholder::NextTierRemaining,
+            "next_tier_discount" => Placeholder::NextTierDiscount,
+            "product_title" => Placeholder::ProductTitle,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for Placeholder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Placeholder::TierQty => "{tier_qty}",
+            Placeholder::Spend => "{spend}",
+            Placeholder::Discount => "{discount}",
+            Placeholder::Percent => "{percent}",
+            Placeholder::Amount => "{amount}",
+            Placeholder::Price => "{price}",
+            Placeholder::SavedAmount => "{saved_amount}",
+            Placeholder::NextTierQty => "{next_tier_qty}",
+            Placeholder::NextTierRemaining => "{next_tier_remaining}",
+            Placeholder::NextTierDiscount => "{next_tier_discount}",
+            Placeholder::ProductTitle => "{product_title}",
+        })
+    }
+}
+
+/// Why a message template was rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TemplateError {
+    /// A `{` has no matching `}`.
+    Unclosed,
+    /// A `}` has no matching `{`.
+    Unopened,
+    UnknownPlaceholder(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Unclosed => f.write_str("has a `{` without a matching `}`"),
+            TemplateError::Unopened => f.write_str("has a `}` without a matching `{`"),
+            TemplateError::UnknownPlaceholder(name) => {
+                write!(f, "has an unknown placeholder `{{{name}}}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+/// A discount message such as `Buy {tier_qty}+, save {discount}`. `{{` and
+/// `}}` write literal braces.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageTemplate {
+    parts: Vec<Part>,
+}
+
+impl MessageTemplate {
+    pub fn parse(template: &str) -> Result<Self, TemplateError> {
+        let mut parts = vec![];
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(TemplateError::Unclosed),
+                        }
+                    }
+                    let placeholder = Placeholder::from_name(name.trim())
+                        .ok_or(TemplateError::UnknownPlaceholder(name))?;
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Placeholder(placeholder));
+                }
+                '}' => return Err(TemplateError::Unopened),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Self { parts })
+    }
+
+    /// The placeholders the template uses, in order.
+    pub fn placeholders(&self) -> impl Iterator<Item = Placeholder> + '_ {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Placeholder(placeholder) => Some(*placeholder),
+            Part::Text(_) => None,
+        })
+    }
+
+    /// Fills in the placeholders with numbers written the way readers of
+    /// `language` expect. Placeholders without a value are left out.
+    pub fn render(&self, values: &MessageValues, language: Option<&str>) -> String {
+        let format = NumberFormat::for_language(language);
+        let amount = |amount: f64| format.number(amount, values.currency_decimals);
+        let quantity = |quantity: i32| format.number(f64::from(quantity), 0);
+
+        let mut message = String::new();
+        for part in &self.parts {
+            let value = match part {
+                Part::Text(text) => Some(text.clone()),
+                Part::Placeholder(placeholder) => match placeholder {
+                    Placeholder::TierQty => values.tier_qty.map(quantity),
+                    Placeholder::Spend => values.spend.map(amount),
+                    Placeholder::Discount => values
+                        .percent
+                        .map(|percent| format!("{}%", format.percentage(percent)))
+                        .or(values.amount.map(amount))
+                        .or(values.price.map(amount)),
+                    Placeholder::Percent => {
+                        values.percent.map(|percent| format.percentage(percent))
+                    }
+                    Placeholder::Amount => values.amount.map(amount),
+                    Placeholder::Price => values.price.map(amount),
+                    Placeholder::SavedAmount => values.saved_amount.map(amount),
+                    Placeholder::NextTierQty => values.next_tier_qty.map(quantity),
+                    Placeholder::NextTierRemaining => values
+                        .remaining_qty
+                        .map(quantity)
+                        .or(values.remaining_spend.map(amount)),
+                    Placeholder::NextTierDiscount => values
+                        .next_tier_percent
+                        .map(|percent| format!("{}%", format.percentage(percent)))
+                        .or(values.next_tier_amount.map(amount)),
+                    Placeholder::ProductTitle => values.product_title.map(str::to_string),
+                },
+            };
+            message.extend(value);
+        }
+        message
+    }
+}
+
+/// What a message's placeholders are filled in with. Amounts are in the
+/// presentment currency.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MessageValues<'a> {
+    pub tier_qty: Option<i32>,
+    pub spend: Option<f64>,
+    pub percent: Option<f64>,
+    pub amount: Option<f64>,
+    pub price: Option<f64>,
+    pub saved_amount: Option<f64>,
+    pub next_tier_qty: Option<i32>,
+    /// The units still missing for a `qty` next tier.
+    pub remaining_qty: Option<i32>,
+    /// The spend still missing for a `spend` next tier.
+    pub remaining_spend: Option<f64>,
+    pub next_tier_percent: Option<f64>,
+    /// The next tier's fixed amount or unit price.
+    pub next_tier_amount: Option<f64>,
+    pub product_title: Option<&'a str>,
+    /// How many decimal places amounts are written with.
+    pub currency_decimals: i32,
+}
+
+// Decimal and thousands separators, by base language. Languages that aren't
+// listed write `1,234.5`.
+const COMMA_DECIMAL_POINT_GROUPED: [&str; 13] = [
+    "DA", "DE", "EL", "ES", "HR", "ID", "IT", "NL", "PT", "RO", "SL", "SR", "TR",
+];
+const COMMA_DECIMAL_SPACE_GROUPED: [&str; 15] = [
+    "BG", "CS", "ET", "FI", "FR", "HU", "LT", "LV", "NB", "NO", "PL", "RU", "SK", "SV", "UK",
+];
+
+struct NumberFormat {
+    decimal_separator: char,
+    group_separator: char,
+}
+
+impl NumberFormat {
+    fn for_language(language: Option<&str>) -> Self {
+        let base_language = language
+            .and_then(|language| language.split('_').next())
+            .unwrap_or_default();
+        if COMMA_DECIMAL_POINT_GROUPED.contains(&base_language) {
+            NumberFormat {
+                decimal_separator: ',',
+                group_separator: '.',
+            }
+        } else if COMMA_DECIMAL_SPACE_GROUPED.contains(&base_language) {
+            NumberFormat {
+                decimal_separator: ',',
+                group_separator: '\u{a0}',
+            }
+        } else {
+            NumberFormat {
+                decimal_separator: '.',
+                group_separator: ',',
+            }
+        }
+    }
+
+    fn number(&self, number: f64, decimals: i32) -> String {
+        let formatted = format!("{:.*}", decimals.max(0) as usize, number.abs());
+        let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+        let mut grouped = String::new();
+        if number < 0.0
+            && formatted
+                .bytes()
+                .any(|digit| digit.is_ascii_digit() && digit != b'0')
+        {
+            grouped.push('-');
+        }
+        for (index, digit) in integer.chars().enumerate() {
+            if index > 0 && (integer.len() - index) % 3 == 0 {
+                grouped.push(self.group_separator);
+            }
+            grouped.push(digit);
+        }
+        if !fraction.is_empty() {
+            grouped.push(self.decimal_separator);
+            grouped.push_str(fraction);
+        }
+        grouped
+    }
+
+    // Up to two decimal places, without trailing zeros.
+    fn percentage(&self, percentage: f64) -> String {
+        let number = self.number(percentage, 2);
+        if number.contains(self.decimal_separator) {
+            number
+                .trim_end_matches('0')
+                .trim_end_matches(self.decimal_separator)
+                .to_string()
+        } else {
+            number
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, values: MessageValues, language: Option<&str>) -> String {
+        MessageTemplate::parse(template)
+            .expect("template is valid")
+            .render(&values, language)
+    }
+
+    #[test]
+    fn rejects_an_unclosed_placeholder() {
+        assert_eq!(
+            MessageTemplate::parse("Buy {qty+"),
+            Err(TemplateError::Unclosed)
+        );
+    }
+
+    #[test]
+    fn rejects_an_unopened_placeholder() {
+        assert_eq!(
+            MessageTemplate::parse("Buy qty}+"),
+            Err(TemplateError::Unopened)
+        );
+    }
+
+    #[test]
+    fn rejects_an_unknown_placeholder() {
+        assert_eq!(
+            MessageTemplate::parse("Save {percnt}%"),
+            Err(TemplateError::UnknownPlaceholder("percnt".to_string()))
+        );
+    }
+
+    #[test]
+    fn writes_escaped_braces_literally() {
+        assert_eq!(
+            render(
+                "{{qty}} is {qty}}}",
+                MessageValues {
+                    tier_qty: Some(5),
+                    ..MessageValues::default()
+                },
+                None
+            ),
+            "{qty} is 5}"
+        );
+    }
+
+    #[test]
+    fn trims_whitespace_in_placeholders() {
+        let template = MessageTemplate::parse("Buy { qty }+").unwrap();
+        assert_eq!(
+            template.placeholders().collect::<Vec<_>>(),
+            [Placeholder::TierQty]
+        );
+    }
+
+    #[test]
+    fn leaves_out_placeholders_without_a_value() {
+        assert_eq!(
+            render("Save {amount}", MessageValues::default(), None),
+            "Save "
+        );
+    }
+
+    #[test]
+    fn groups_thousands_by_language() {
+        let values = MessageValues {
+            saved_amount: Some(1234567.891),
+            currency_decimals: 2,
+            ..MessageValues::default()
+        };
+        assert_eq!(render("{saved_amount}", values, None), "1,234,567.89");
+        assert_eq!(render("{saved_amount}", values, Some("DE")), "1.234.567,89");
+        assert_eq!(
+            render("{saved_amount}", values, Some("FR")),
+            "1\u{a0}234\u{a0}567,89"
+        );
+        assert_eq!(
+            render("{saved_amount}", values, Some("PT_BR")),
+            "1.234.567,89"
+        );
+    }
+
+    #[test]
+    fn writes_negative_numbers_with_a_sign() {
+        let values = |amount| MessageValues {
+            saved_amount: Some(amount),
+            currency_decimals: 2,
+            ..MessageValues::default()
+        };
+        assert_eq!(render("{saved_amount}", values(-1234.5), None), "-1,234.50");
+        // Amounts that round to zero don't keep their sign.
+        assert_eq!(render("{saved_amount}", values(-0.001), None), "0.00");
+    }
+
+    #[test]
+    fn trims_trailing_zeros_from_percentages() {
+        let values = |percent| MessageValues {
+            percent: Some(percent),
+            ..MessageValues::default()
+        };
+        assert_eq!(render("{discount}", values(10.0), None), "10%");
+        assert_eq!(render("{discount}", values(12.5), None), "12.5%");
+        assert_eq!(render("{percent}", values(12.345), None), "12.35");
+        assert_eq!(render("{percent}", values(7.5), Some("DE")), "7,5");
+    }
+}
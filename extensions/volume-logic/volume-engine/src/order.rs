@@ -0,0 +1,95 @@
+use crate::cart::Cart;
+use crate::config::next_tier;
+use crate::config::DiscountConfig;
+use crate::config::Threshold;
+use crate::config::TierConfig;
+use crate::config::TierValue;
+use crate::config::Volume;
+use crate::currency::Currency;
+use crate::decision::DiscountValue;
+use crate::decision::OrderCondition;
+use crate::decision::OrderDiscount;
+use crate::message::MessageValues;
+
+/// One discount per order tier the cart meets. Shopify applies the one worth
+/// the most.
+pub fn order_discounts(cart: &Cart) -> Vec<OrderDiscount> {
+    // Callers report configuration errors.
+    let Some(Ok(discount_config)) = DiscountConfig::from_cart(cart) else {
+        return vec![];
+    };
+
+    let (excluded_lines, lines): (Vec<_>, Vec<_>) = cart
+        .lines
+        .iter()
+        .partition(|line| discount_config.exclude.excludes(&line.merchandise));
+    let excluded_line_ids: Vec<String> =
+        excluded_lines.iter().map(|line| line.id.clone()).collect();
+    let line_ids: Vec<String> = lines.iter().map(|line| line.id.clone()).collect();
+
+    let volume = Volume {
+        quantity: lines.iter().map(|line| line.quantity).sum(),
+        subtotal: lines.iter().map(|line| line.subtotal).sum(),
+        weight: 0.0,
+    };
+
+    let currency = Currency::of(cart);
+    let tiers: Vec<TierConfig> = discount_config
+        .order_tiers
+        .iter()
+        .map(|tier| tier.in_currency(&currency))
+        .collect();
+    // Eligibility and messages use the presentment amounts, while Shopify
+    // checks the condition's subtotal in the shop currency.
+    discount_config
+        .order_tiers
+        .iter()
+        .zip(&tiers)
+        .filter(|(_, tier)| tier.threshold.is_met_by(&volume))
+        .filter_map(|(shop_tier, tier)| {
+            let condition = match shop_tier.threshold {
+                Threshold::Quantity(quantity) => OrderCondition::MinimumQuantity {
+                    quantity,
+                    line_ids: line_ids.clone(),
+                },
+                Threshold::Subtotal(amount) => OrderCondition::MinimumSubtotal {
+                    amount,
+                    excluded_line_ids: excluded_line_ids.clone(),
+                },
+                // Rejected when the configuration is parsed.
+                Threshold::Weight(_) => return None,
+            };
+            let value = match tier.value {
+                TierValue::Percentage(percentage) => DiscountValue::Percentage(percentage),
+                TierValue::FixedAmount(amount) => DiscountValue::FixedAmount {
+                    amount,
+                    applies_to_each_item: false,
+                },
+                TierValue::UnitPrice(_) => return None,
+            };
+
+            let saved_amount = match tier.value {
+                TierValue::Percentage(percentage) => volume.subtotal * percentage / 100.0,
+                TierValue::FixedAmount(amount) => amount.min(volume.subtotal),
+                TierValue::UnitPrice(_) => return None,
+            };
+            let message = tier.message(
+                cart.language.as_deref(),
+                next_tier(&tiers, tier),
+                &volume,
+                MessageValues {
+                    saved_amount: Some(currency.round(saved_amount)),
+                    currency_decimals: currency.decimals(),
+                    ..MessageValues::default()
+                },
+            );
+
+            Some(OrderDiscount {
+                value,
+                message,
+                excluded_line_ids: excluded_line_ids.clone(),
+                conditions: vec![condition],
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,281 @@
+use crate::cart::Cart;
+use crate::cart::Line;
+use crate::cart::Merchandise;
+use crate::config::Aggregation;
+use crate::config::ConfigError;
+use crate::config::CustomerSegment;
+use crate::config::DiscountConfig;
+use crate::config::Threshold;
+use crate::config::TierConfig;
+use crate::config::TierSet;
+use crate::config::TierSetOverrides;
+use crate::config::TierValue;
+use crate::config::Volume;
+use crate::config::DISCOUNT_TIER_SET_KEY;
+use crate::currency::Currency;
+use crate::decision::DiscountValue;
+use crate::decision::LineDiscount;
+use crate::decision::NextTier;
+use crate::decision::ProductDecision;
+use crate::message::MessageValues;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Prices every cart line whose product has a valid tier configuration. An
+/// invalid discount configuration prices nothing, since its exclusions and
+/// targeting rule can't be honoured. Callers report its error.
+pub fn product_discounts(cart: &Cart) -> ProductDecision {
+    let mut decision = ProductDecision::default();
+
+    let discount_config = match DiscountConfig::from_cart(cart) {
+        Some(Ok(discount_config)) => Some(discount_config),
+        Some(Err(_)) => return decision,
+        None => None,
+    };
+
+    // A company location's tiers win over its company's, and a B2B buyer's
+    // tiers win over their market's.
+    let owners = cart
+        .purchasing_company
+        .iter()
+        .flat_map(|purchasing_company| [&purchasing_company.location, &purchasing_company.company])
+        .chain(&cart.market);
+    let mut overrides = vec![];
+    for owner in owners {
+        let Some(json) = &owner.volume_discount_overrides else {
+            continue;
+        };
+        match TierSetOverrides::parse(json) {
+            Ok(tier_set_overrides) => overrides.push(tier_set_overrides),
+            Err(error) => decision.override_errors.push((owner.id.clone(), error)),
+        }
+    }
+
+    let segments: Vec<&CustomerSegment> = discount_config
+        .iter()
+        .flat_map(|discount_config| &discount_config.segments)
+        .filter(|segment| segment.matches(cart.customer.as_ref(), cart.presentment_currency_rate))
+        .collect();
+
+    // Tiers are configured in the shop currency, while cart amounts are in
+    // the buyer's presentment currency.
+    let currency = Currency::of(cart);
+    let mut volume_lines = vec![];
+    for line in &cart.lines {
+        match volume_line(
+            line,
+            discount_config.as_ref(),
+            &overrides,
+            cart.country.as_deref(),
+        ) {
+            Some(Ok(mut volume_line)) => {
+                for segment in &segments {
+                    volume_line.tier_set = Cow::Owned(volume_line.tier_set.layered(&segment.tiers));
+                }
+                volume_line.tier_set = Cow::Owned(volume_line.tier_set.in_currency(&currency));
+                volume_lines.push(volume_line);
+            }
+            Some(Err(error)) => decision.config_errors.push(error),
+            None => {}
+        }
+    }
+
+    let mut group_volumes: HashMap<&str, Volume> = HashMap::new();
+    for volume_line in &volume_lines {
+        let volume = group_volumes.entry(volume_line.group.as_str()).or_default();
+        volume.quantity += volume_line.line.quantity;
+        volume.subtotal += volume_line.line.subtotal;
+        volume.weight += volume_line.unit_weight * f64::from(volume_line.line.quantity);
+    }
+
+    for volume_line in &volume_lines {
+        let group_volume = &group_volumes[volume_line.group.as_str()];
+        let tier = best_tier(volume_line, group_volume);
+        let next_tier = volume_line.tier_set.next_tier(group_volume, tier);
+        if let Some(next_tier) = next_tier {
+            decision.next_tiers.push(NextTier {
+                line_id: volume_line.line.id.clone(),
+                threshold: next_tier.threshold,
+                remaining: match next_tier.threshold.remaining(group_volume) {
+                    Threshold::Subtotal(subtotal) => Threshold::Subtotal(currency.round(subtotal)),
+                    remaining => remaining,
+                },
+                value: next_tier.value,
+            });
+        }
+        let Some(tier) = tier else {
+            continue;
+        };
+        decision.discounts.extend(line_discount(
+            volume_line,
+            tier,
+            next_tier,
+            group_volume,
+            &currency,
+            cart.language.as_deref(),
+        ));
+    }
+
+    decision
+}
+
+// The group of every line priced by the discount's own tiers. Other group
+// keys are GIDs or JSON, so this can't collide with them.
+const DISCOUNT_GROUP: &str = "discount";
+
+// A cart line with a valid tier set, and the key of the group whose combined
+// volume decides its tier.
+struct VolumeLine<'a> {
+    line: &'a Line,
+    tier_set: Cow<'a, TierSet>,
+    group: String,
+    // In kilograms. Variants without a weight don't count toward weight tiers.
+    unit_weight: f64,
+}
+
+// A product's own tiers take precedence over the discount's tiers, and the
+// first of `overrides` that replaces them for the buyer's country takes
+// precedence over both.
+fn volume_line<'a>(
+    line: &'a Line,
+    discount_config: Option<&'a DiscountConfig>,
+    overrides: &'a [TierSetOverrides],
+    country: Option<&str>,
+) -> Option<Result<VolumeLine<'a>, (String, ConfigError)>> {
+    let Merchandise::Variant(variant) = &line.merchandise else {
+        return None;
+    };
+    if discount_config.is_some_and(|discount_config| !discount_config.includes(&line.merchandise)) {
+        return None;
+    }
+
+    let Some(tiers_json) = &variant.product.volume_discount else {
+        let discount_config =
+            discount_config.filter(|discount_config| discount_config.targets(&variant.product))?;
+        let replacement = overrides.iter().find_map(|tier_set_overrides| {
+            tier_set_overrides.replacement(
+                Some(DISCOUNT_TIER_SET_KEY),
+                &variant.product.id,
+                country,
+            )
+        });
+        // Replaced tiers pool with the discount's unless they ask for less.
+        let group = match replacement.map(|replacement| replacement.aggregation) {
+            Some(Aggregation::Line) => line.id.clone(),
+            Some(Aggregation::Product) => variant.product.id.clone(),
+            _ => DISCOUNT_GROUP.to_string(),
+        };
+        return Some(Ok(VolumeLine {
+            line,
+            tier_set: Cow::Borrowed(replacement.unwrap_or(&discount_config.tiers)),
+            group,
+            unit_weight: variant.weight,
+        }));
+    };
+    let tier_set = match TierSet::parse(tiers_json) {
+        Ok(tier_set) => tier_set,
+        Err(error) => return Some(Err((variant.product.id.clone(), error))),
+    };
+
+    let replacement = overrides.iter().find_map(|tier_set_overrides| {
+        tier_set_overrides.replacement(tier_set.id.as_deref(), &variant.product.id, country)
+    });
+
+    // Products that share a metaobject carry identical copies of it, so the
+    // raw value identifies it when the copy doesn't include the metaobject ID.
+    // Replaced tiers still pool by the metaobject they replace.
+    let group = match replacement.unwrap_or(&tier_set).aggregation {
+        Aggregation::Line => line.id.clone(),
+        Aggregation::Product => variant.product.id.clone(),
+        Aggregation::Metaobject => tier_set.id.clone().unwrap_or_else(|| tiers_json.clone()),
+        Aggregation::Discount => DISCOUNT_GROUP.to_string(),
+    };
+
+    Some(Ok(VolumeLine {
+        line,
+        tier_set: match replacement {
+            Some(replacement) => Cow::Borrowed(replacement),
+            None => Cow::Owned(tier_set),
+        },
+        group,
+        unit_weight: variant.weight,
+    }))
+}
+
+// The qualifying tier that saves the line the most.
+fn best_tier<'a>(volume_line: &'a VolumeLine, group_volume: &Volume) -> Option<&'a TierConfig> {
+    volume_line
+        .tier_set
+        .qualifying_tiers(group_volume)
+        .into_iter()
+        .max_by(|a, b| {
+            line_savings(volume_line.line, &a.value)
+                .partial_cmp(&line_savings(volume_line.line, &b.value))
+                .unwrap_or(Ordering::Equal)
+        })
+}
+
+fn line_discount(
+    volume_line: &VolumeLine,
+    tier: &TierConfig,
+    next_tier: Option<&TierConfig>,
+    group_volume: &Volume,
+    currency: &Currency,
+    language: Option<&str>,
+) -> Option<LineDiscount> {
+    let product_title = match &volume_line.line.merchandise {
+        Merchandise::Variant(variant) => Some(variant.product.title.as_str()),
+        _ => None,
+    };
+    let message = tier.message(
+        language,
+        next_tier,
+        group_volume,
+        MessageValues {
+            saved_amount: Some(currency.round(line_savings(volume_line.line, &tier.value))),
+            product_title,
+            currency_decimals: currency.decimals(),
+            ..MessageValues::default()
+        },
+    );
+
+    Some(LineDiscount {
+        line_id: volume_line.line.id.clone(),
+        value: discount_value(volume_line.line, &tier.value, currency)?,
+        message,
+    })
+}
+
+// Converts a tier value into a discount on `line`. Fixed amounts are capped at
+// the unit price, and unit prices above the current price yield no discount.
+fn discount_value(line: &Line, value: &TierValue, currency: &Currency) -> Option<DiscountValue> {
+    match *value {
+        TierValue::Percentage(percentage) => Some(DiscountValue::Percentage(percentage)),
+        TierValue::FixedAmount(amount) => Some(DiscountValue::FixedAmount {
+            amount: currency.round(amount.min(line.unit_amount)),
+            applies_to_each_item: true,
+        }),
+        TierValue::UnitPrice(price) => {
+            let amount = currency.round(line.subtotal - price * f64::from(line.quantity));
+            if amount <= 0.0 {
+                return None;
+            }
+            Some(DiscountValue::FixedAmount {
+                amount,
+                applies_to_each_item: false,
+            })
+        }
+    }
+}
+
+// How much a tier value takes off `line`, in the cart currency.
+fn line_savings(line: &Line, value: &TierValue) -> f64 {
+    let quantity = f64::from(line.quantity);
+
+    match *value {
+        TierValue::Percentage(percentage) => line.subtotal * percentage / 100.0,
+        TierValue::FixedAmount(amount) => amount.min(line.unit_amount) * quantity,
+        TierValue::UnitPrice(price) => (line.subtotal - price * quantity).max(0.0),
+    }
+}
@@ -0,0 +1,58 @@
+use crate::cart::Product;
+use serde::Deserialize;
+
+/// A predicate over a product that decides whether its cart lines take part
+/// in volume pricing.
+///
+/// Written in the discount configuration as nested single-key objects, for
+/// example `{ "all": [{ "has_any_tag": ["wholesale"] }, { "not": { "vendor": ["Acme"] } }] }`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Rule {
+    /// At least one of the rules matches.
+    Any(Vec<Rule>),
+    /// Every rule matches.
+    All(Vec<Rule>),
+    /// The rule doesn't match.
+    Not(Box<Rule>),
+    /// The product has at least one of the tags.
+    HasAnyTag(Vec<String>),
+    /// The product's vendor is one of these, ignoring ASCII case.
+    Vendor(Vec<String>),
+    /// The product's type is one of these, ignoring ASCII case.
+    ProductType(Vec<String>),
+}
+
+impl Rule {
+    pub fn matches(&self, product: &Product) -> bool {
+        match self {
+            Rule::Any(rules) => rules.iter().any(|rule| rule.matches(product)),
+            Rule::All(rules) => rules.iter().all(|rule| rule.matches(product)),
+            Rule::Not(rule) => !rule.matches(product),
+            Rule::HasAnyTag(tags) => tags.iter().any(|tag| product.tags.contains(tag)),
+            Rule::Vendor(vendors) => is_one_of(product.vendor.as_deref(), vendors),
+            Rule::ProductType(product_types) => {
+                is_one_of(product.product_type.as_deref(), product_types)
+            }
+        }
+    }
+
+    /// Every tag the rule refers to. The input query only reports the tags
+    /// it's asked about, so each of these has to be requested.
+    pub fn tags(&self) -> Vec<&str> {
+        match self {
+            Rule::Any(rules) | Rule::All(rules) => rules.iter().flat_map(Rule::tags).collect(),
+            Rule::Not(rule) => rule.tags(),
+            Rule::HasAnyTag(tags) => tags.iter().map(String::as_str).collect(),
+            Rule::Vendor(_) | Rule::ProductType(_) => vec![],
+        }
+    }
+}
+
+fn is_one_of(value: Option<&str>, candidates: &[String]) -> bool {
+    value.is_some_and(|value| {
+        candidates
+            .iter()
+            .any(|candidate| candidate.eq_ignore_ascii_case(value))
+    })
+}
@@ -0,0 +1,258 @@
+//! Property tests for tier selection. Tier sets are generated as the JSON a
+//! merchant would write, so parsing and validation are exercised too.
+
+use proptest::prelude::*;
+use serde_json::json;
+use volume_engine::cart::{Cart, Line, Merchandise, Product, TierSetOwner, Variant};
+use volume_engine::config::{Threshold, TierSet, TierValue, Volume};
+use volume_engine::decision::DiscountValue;
+use volume_engine::product::product_discounts;
+
+// Valid percentage tiers: distinct thresholds with discounts that grow with
+// the threshold, written in a random order.
+fn percentage_tiers() -> impl Strategy<Value = Vec<(i32, f64)>> {
+    (
+        prop::collection::btree_set(0..500i32, 1..8),
+        prop::collection::vec(0.0..=100.0f64, 8),
+    )
+        .prop_flat_map(|(thresholds, mut discounts)| {
+            discounts.truncate(thresholds.len());
+            discounts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let tiers: Vec<(i32, f64)> = thresholds.into_iter().zip(discounts).collect();
+            Just(tiers).prop_shuffle()
+        })
+}
+
+// Valid tiers of any value kind. Each kind is monotonic on its own.
+fn mixed_tiers() -> impl Strategy<Value = Vec<serde_json::Value>> {
+    prop::collection::btree_set(0..500i32, 1..8).prop_flat_map(|thresholds| {
+        let len = thresholds.len();
+        (
+            Just(thresholds),
+            prop::collection::vec(0..3u8, len),
+            prop::collection::vec(0.0..=100.0f64, len),
+        )
+            .prop_map(|(thresholds, kinds, mut amounts)| {
+                amounts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                let unit_prices: Vec<f64> = amounts.iter().rev().copied().collect();
+                thresholds
+                    .into_iter()
+                    .zip(kinds)
+                    .enumerate()
+                    .map(|(index, (qty, kind))| match kind {
+                        0 => json!({ "qty": qty, "discount": amounts[index], "label": "%" }),
+                        1 => json!({
+                            "qty": qty,
+                            "kind": "fixed_amount",
+                            "discount": amounts[index],
+                            "label": "$ off",
+                        }),
+                        _ => json!({
+                            "qty": qty,
+                            "kind": "unit_price",
+                            "price": unit_prices[index],
+                            "label": "$ each",
+                        }),
+                    })
+                    .collect()
+            })
+    })
+}
+
+fn tier_set(tiers: &[(i32, f64)]) -> TierSet {
+    let tiers: Vec<_> = tiers
+        .iter()
+        .map(|&(qty, discount)| json!({ "qty": qty, "discount": discount, "label": "tier" }))
+        .collect();
+    TierSet::parse(&json!({ "version": 1, "tiers": tiers }).to_string())
+        .expect("generated tiers are valid")
+}
+
+fn quantity(quantity: i32) -> Volume {
+    Volume {
+        quantity,
+        ..Volume::default()
+    }
+}
+
+fn percentage_at(tier_set: &TierSet, quantity_in_cart: i32) -> f64 {
+    match tier_set
+        .qualifying_tiers(&quantity(quantity_in_cart))
+        .as_slice()
+    {
+        [] => 0.0,
+        [tier] => match tier.value {
+            TierValue::Percentage(percentage) => percentage,
+            _ => unreachable!("only percentage tiers are generated"),
+        },
+        tiers => panic!("quantity tiers can't qualify together: {tiers:?}"),
+    }
+}
+
+fn single_line_cart(tiers: &[serde_json::Value], quantity: i32, unit_amount: f64) -> Cart {
+    Cart {
+        lines: vec![Line {
+            id: "gid://shopify/CartLine/0".to_string(),
+            quantity,
+            unit_amount,
+            subtotal: unit_amount * f64::from(quantity),
+            merchandise: Merchandise::Variant(Box::new(Variant {
+                id: "gid://shopify/ProductVariant/0".to_string(),
+                product: Product {
+                    id: "gid://shopify/Product/0".to_string(),
+                    title: "Widget".to_string(),
+                    volume_discount: Some(json!({ "version": 1, "tiers": tiers }).to_string()),
+                    in_discount_collections: false,
+                    tags: vec![],
+                    vendor: None,
+                    product_type: None,
+                    is_gift_card: false,
+                },
+                weight: 0.0,
+                sku: None,
+                requires_shipping: true,
+            })),
+        }],
+        presentment_currency_rate: 1.0,
+        ..Cart::default()
+    }
+}
+
+#[test]
+fn invalid_overrides_keep_the_base_tiers() {
+    let mut cart = single_line_cart(
+        &[json!({ "qty": 5, "discount": 10.0, "label": "5+" })],
+        6,
+        10.0,
+    );
+    cart.market = Some(TierSetOwner {
+        id: "gid://shopify/Market/1".to_string(),
+        volume_discount_overrides: Some(r#"{"version":2}"#.to_string()),
+    });
+
+    let decision = product_discounts(&cart);
+    assert_eq!(decision.discounts.len(), 1);
+    assert_eq!(decision.discounts[0].value, DiscountValue::Percentage(10.0));
+    assert!(decision.config_errors.is_empty());
+    assert_eq!(decision.override_errors.len(), 1);
+    assert_eq!(decision.override_errors[0].0, "gid://shopify/Market/1");
+}
+
+proptest! {
+    #[test]
+    fn more_quantity_never_lowers_the_discount(
+        tiers in percentage_tiers(),
+        smaller in 0..600i32,
+        extra in 0..600i32,
+    ) {
+        let tier_set = tier_set(&tiers);
+        prop_assert!(percentage_at(&tier_set, smaller + extra) >= percentage_at(&tier_set, smaller));
+    }
+
+    #[test]
+    fn layered_segment_tiers_never_lower_the_discount(
+        base in percentage_tiers(),
+        segment in percentage_tiers(),
+        smaller in 0..600i32,
+        extra in 0..600i32,
+    ) {
+        let layered = tier_set(&base).layered(&tier_set(&segment).tiers);
+        prop_assert!(percentage_at(&layered, smaller + extra) >= percentage_at(&layered, smaller));
+    }
+
+    #[test]
+    fn chooses_the_highest_qualifying_threshold(
+        tiers in percentage_tiers(),
+        quantity_in_cart in 0..600i32,
+    ) {
+        let tier_set = tier_set(&tiers);
+        let expected = tiers
+            .iter()
+            .map(|&(qty, _)| qty)
+            .filter(|&qty| qty <= quantity_in_cart)
+            .max();
+        let chosen = tier_set
+            .qualifying_tiers(&quantity(quantity_in_cart))
+            .first()
+            .map(|tier| tier.threshold);
+        prop_assert_eq!(chosen, expected.map(Threshold::Quantity));
+    }
+
+    #[test]
+    fn next_tier_is_the_lowest_threshold_not_yet_met(
+        tiers in percentage_tiers(),
+        quantity_in_cart in 1..600i32,
+    ) {
+        let tiers_json: Vec<_> = tiers
+            .iter()
+            .map(|&(qty, discount)| json!({ "qty": qty, "discount": discount, "label": "tier" }))
+            .collect();
+        let cart = single_line_cart(&tiers_json, quantity_in_cart, 10.0);
+        let expected = tiers
+            .iter()
+            .map(|&(qty, _)| qty)
+            .filter(|&qty| qty > quantity_in_cart)
+            .min();
+        let next = product_discounts(&cart)
+            .next_tiers
+            .first()
+            .map(|next_tier| (next_tier.threshold, next_tier.remaining));
+        prop_assert_eq!(
+            next,
+            expected.map(|qty| {
+                (Threshold::Quantity(qty), Threshold::Quantity(qty - quantity_in_cart))
+            })
+        );
+    }
+
+    #[test]
+    fn percentages_never_exceed_100(
+        tiers in mixed_tiers(),
+        quantity_in_cart in 1..600i32,
+        unit_amount in 0.01..500.0f64,
+    ) {
+        let cart = single_line_cart(&tiers, quantity_in_cart, unit_amount);
+        for discount in product_discounts(&cart).discounts {
+            if let DiscountValue::Percentage(percentage) = discount.value {
+                prop_assert!((0.0..=100.0).contains(&percentage));
+            }
+        }
+    }
+
+    #[test]
+    fn discounts_never_exceed_the_line_subtotal(
+        tiers in mixed_tiers(),
+        quantity_in_cart in 1..600i32,
+        unit_amount in 0.01..500.0f64,
+    ) {
+        let cart = single_line_cart(&tiers, quantity_in_cart, unit_amount);
+        let line = &cart.lines[0];
+        for discount in product_discounts(&cart).discounts {
+            let total = match discount.value {
+                DiscountValue::Percentage(percentage) => line.subtotal * percentage / 100.0,
+                DiscountValue::FixedAmount { amount, applies_to_each_item: true } => {
+                    amount * f64::from(line.quantity)
+                }
+                DiscountValue::FixedAmount { amount, applies_to_each_item: false } => amount,
+            };
+            // Amounts are rounded to cents, which can add up to half a cent per unit.
+            let tolerance = 0.005 * f64::from(line.quantity);
+            prop_assert!(total <= line.subtotal + tolerance, "{total} > {}", line.subtotal);
+        }
+    }
+
+    #[test]
+    fn duplicate_thresholds_are_rejected(
+        tiers in percentage_tiers(),
+        duplicate in any::<prop::sample::Index>(),
+    ) {
+        let mut tiers = tiers;
+        let (qty, discount) = tiers[duplicate.index(tiers.len())];
+        tiers.push((qty, discount));
+        let tiers: Vec<_> = tiers
+            .iter()
+            .map(|&(qty, discount)| json!({ "qty": qty, "discount": discount, "label": "tier" }))
+            .collect();
+        prop_assert!(TierSet::parse(&json!(tiers).to_string()).is_err());
+    }
+}